@@ -0,0 +1,148 @@
+use std::fs::{ self, File };
+use std::io::{ self, BufReader };
+use std::path::{ Path, PathBuf };
+use std::time::Duration;
+
+use crate::cancel;
+use crate::error::PuzzleError;
+use crate::params::Params;
+use crate::pool;
+use crate::registry::Puzzle;
+use crate::runner::Run;
+
+/// The outcome of solving one of the inputs in a batch.
+#[derive(Debug)]
+pub struct BatchResult {
+    pub path: PathBuf,
+    pub outcome: Result<Run, PuzzleError>,
+}
+
+/// The files matched by `pattern`: every file in it if it is a directory, or the
+/// files whose names match it if its last component has `*` or `?` wildcards, or
+/// otherwise the file itself. Matches are sorted by name.
+pub fn find_inputs(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let path = Path::new(pattern);
+    if path.is_dir() {
+        return list_files(path, |_| true);
+    }
+
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    list_files(dir, |file_name| glob_match(name, file_name))
+}
+
+/// The files directly inside `dir` whose names pass `filter`, sorted by name.
+fn list_files(dir: &Path, filter: impl Fn(&str) -> bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let matches = entry.file_name().to_str().is_some_and(&filter);
+        if matches && entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of characters
+/// and `?` for any one character.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Where to resume if the characters after the last `*` stop matching
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match backtrack {
+                // Let the `*` swallow one more character
+                Some((star, start)) => {
+                    backtrack = Some((star, start + 1));
+                    p = star + 1;
+                    n = start + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Solves the puzzle with each of `paths` as its input, up to `jobs` at once,
+/// giving up on each after `timeout` if there is one. The results are in the same
+/// order as `paths`.
+pub fn solve(
+    puzzle: &Puzzle,
+    paths: &[PathBuf],
+    part: Option<u8>,
+    params: &Params,
+    timeout: Option<Duration>,
+    jobs: usize,
+) -> Vec<BatchResult> {
+    pool::map(paths, jobs, |path| {
+        let outcome = File::open(path)
+            .map_err(PuzzleError::from)
+            .and_then(|file| cancel::with_timeout(timeout, || puzzle.run_with(BufReader::new(file), part, params)));
+        BatchResult { path: path.clone(), outcome }
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use crate::solution::Answer;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("day15*.txt", "day15-alice.txt"));
+        assert!(glob_match("day15*.txt", "day15.txt"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a?c*", "abcabc"));
+        assert!(glob_match("*b*b", "abcabcb"));
+        assert!(!glob_match("day15*.txt", "day16-alice.txt"));
+        assert!(!glob_match("a?c", "ac"));
+        assert!(!glob_match("*.txt", "input.toml"));
+    }
+
+    #[test]
+    fn test_solve() {
+        let dir = std::env::temp_dir().join(format!("advent-of-code-2015-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "(()))(").unwrap();
+        fs::write(dir.join("b.txt"), "(((").unwrap();
+        fs::write(dir.join("c.txt"), ")").unwrap();
+        fs::write(dir.join("notes.md"), "").unwrap();
+
+        let paths = find_inputs(dir.join("*.txt").to_str().unwrap()).unwrap();
+        assert_eq!(vec![dir.join("a.txt"), dir.join("b.txt"), dir.join("c.txt")], paths);
+        assert_eq!(4, find_inputs(dir.to_str().unwrap()).unwrap().len());
+
+        let puzzle = registry::get(1).unwrap();
+        let missing = vec![dir.join("missing.txt")];
+        let results = solve(puzzle, &[paths, missing].concat(), Some(1), &puzzle.params(), None, 2);
+        let answers: Vec<Option<Answer>> = results.iter()
+            .map(|result| result.outcome.as_ref().ok().map(|run| run.solved().next().unwrap().1.answer.clone()))
+            .collect();
+        assert_eq!(vec![Some(Answer::Number(0)), Some(Answer::Number(3)), Some(Answer::Number(-1)), None], answers);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
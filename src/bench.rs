@@ -0,0 +1,145 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::params::Params;
+use crate::registry::Puzzle;
+use crate::runner::Run;
+
+/// Summary statistics over repeated timings of one phase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// Sample standard deviation, or zero for a single sample.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+
+        let secs: Vec<f64> = sorted.iter().map(|d| d.as_secs_f64()).collect();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+
+        Some(Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// Timing statistics for each phase of a day's solver.
+#[derive(Debug, Clone)]
+pub struct Bench {
+    pub runs: usize,
+    pub parse: Stats,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+impl Bench {
+    /// The phases that were measured, with their names.
+    pub fn phases(&self) -> impl Iterator<Item = (&'static str, &Stats)> {
+        [("parse", Some(&self.parse)), ("part1", self.part1.as_ref()), ("part2", self.part2.as_ref())]
+            .into_iter()
+            .filter_map(|(name, stats)| stats.map(|stats| (name, stats)))
+    }
+}
+
+/// Solves the given puzzle `warmup` times without measuring, then `runs` more times
+/// (at least once), and summarises the time taken by each phase. The input is
+/// parsed from memory on every run so that reading it does not affect the timings.
+/// Stops at the first error from the solver.
+pub fn bench(puzzle: &Puzzle, input: &str, part: Option<u8>, params: &Params, warmup: usize, runs: usize) -> Result<Bench> {
+    let solve = || puzzle.run_with(Cursor::new(input), part, params).and_then(Run::into_result);
+    let runs = runs.max(1);
+
+    for _ in 0..warmup {
+        solve()?;
+    }
+
+    let mut parse_times = Vec::with_capacity(runs);
+    let mut part1_times = Vec::with_capacity(runs);
+    let mut part2_times = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let run = solve()?;
+        parse_times.push(run.parse_time);
+        part1_times.extend(run.part1.and_then(Result::ok).map(|part_run| part_run.time));
+        part2_times.extend(run.part2.and_then(Result::ok).map(|part_run| part_run.time));
+    }
+
+    Ok(Bench {
+        runs,
+        parse: Stats::from_samples(&parse_times).expect("there is at least one run"),
+        part1: Stats::from_samples(&part1_times),
+        part2: Stats::from_samples(&part2_times),
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day01;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_stats_odd_samples() {
+        let stats = Stats::from_samples(&[ms(4), ms(2), ms(6)]).unwrap();
+        assert_eq!(ms(2), stats.min);
+        assert_eq!(ms(4), stats.median);
+        assert_eq!(ms(4), stats.mean);
+        assert_eq!(ms(2), stats.stddev);
+    }
+
+    #[test]
+    fn test_stats_even_samples() {
+        let stats = Stats::from_samples(&[ms(1), ms(3), ms(2), ms(10)]).unwrap();
+        assert_eq!(ms(1), stats.min);
+        assert_eq!(Duration::from_micros(2500), stats.median);
+        assert_eq!(ms(4), stats.mean);
+    }
+
+    #[test]
+    fn test_stats_single_and_empty() {
+        let stats = Stats::from_samples(&[ms(5)]).unwrap();
+        assert_eq!(ms(5), stats.median);
+        assert_eq!(Duration::ZERO, stats.stddev);
+
+        assert!(Stats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_bench() {
+        let bench = bench(&day01::PUZZLE, "(()))(", Some(1), &Params::default(), 1, 3).unwrap();
+        assert_eq!(3, bench.runs);
+        assert!(bench.part1.is_some());
+        assert!(bench.part2.is_none());
+        assert_eq!(vec!["parse", "part1"], bench.phases().map(|(name, _)| name).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,139 @@
+use std::cell::{ Cell, RefCell };
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::time::{ Duration, Instant };
+
+use crate::error::{ PuzzleError, Result };
+
+/// Lets a solve be stopped from outside, or once it has run for too long.
+///
+/// Solvers do not take a token. Instead, [`with_token`] makes it the current
+/// thread's token while a solve runs, and the loops that can run for a long time
+/// (or forever, on some inputs) call [`check`] to find out whether to give up.
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    /// When to give up, along with the timeout it came from for error messages.
+    deadline: Option<(Instant, Duration)>,
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by calling [`cancel`](Self::cancel).
+    pub fn new() -> Self {
+        CancelToken { cancelled: Arc::new(AtomicBool::new(false)), deadline: None }
+    }
+
+    /// A token that is also cancelled once `timeout` has passed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        CancelToken { deadline: Some((Instant::now() + timeout, timeout)), ..CancelToken::new() }
+    }
+
+    /// Asks the solves using this token, or a clone of it, to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the solve should stop, and why.
+    pub fn check(&self) -> Result<()> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(PuzzleError::Cancelled);
+        }
+        match self.deadline {
+            Some((deadline, timeout)) if Instant::now() >= deadline => Err(PuzzleError::TimedOut(timeout)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        CancelToken::new()
+    }
+}
+
+/// How many calls to [`check`] go by between looking at the token, so that it is
+/// cheap enough to call on every iteration of a hot loop.
+const CHECK_INTERVAL: u32 = 1024;
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+    static CALLS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Runs `f` with `token` as the current thread's token, restoring the previous
+/// token afterwards.
+pub fn with_token<T>(token: &CancelToken, f: impl FnOnce() -> T) -> T {
+    /// Puts the previous token back even if `f` panics.
+    struct Restore(Option<CancelToken>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|current| current.replace(self.0.take()));
+        }
+    }
+
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(token.clone()))));
+    f()
+}
+
+/// Runs `f` with a token that times out after `timeout`, or with no limit if
+/// `timeout` is `None`.
+pub fn with_timeout<T>(timeout: Option<Duration>, f: impl FnOnce() -> T) -> T {
+    match timeout {
+        Some(timeout) => with_token(&CancelToken::with_timeout(timeout), f),
+        None => f(),
+    }
+}
+
+/// Returns an error if the current thread's token has been cancelled or has timed
+/// out, and `Ok` if there is no token. Long-running loops in the solvers call this
+/// on every iteration and return the error with `?`.
+pub fn check() -> Result<()> {
+    let calls = CALLS.with(|calls| {
+        let n = calls.get().wrapping_add(1);
+        calls.set(n);
+        n
+    });
+    if !calls.is_multiple_of(CHECK_INTERVAL) {
+        return Ok(());
+    }
+    CURRENT.with(|current| match &*current.borrow() {
+        Some(token) => token.check(),
+        None => Ok(()),
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_repeatedly() -> Result<()> {
+        for _ in 0..CHECK_INTERVAL {
+            check()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_token() {
+        assert!(check_repeatedly().is_ok());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        assert!(with_token(&token, check_repeatedly).is_ok());
+        token.cancel();
+        assert!(matches!(with_token(&token, check_repeatedly), Err(PuzzleError::Cancelled)));
+        // The token only applies inside with_token
+        assert!(check_repeatedly().is_ok());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let result = with_timeout(Some(Duration::ZERO), check_repeatedly);
+        assert!(matches!(result, Err(PuzzleError::TimedOut(timeout)) if timeout == Duration::ZERO));
+        assert!(with_timeout(None, check_repeatedly).is_ok());
+    }
+}
@@ -0,0 +1,109 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::cancel;
+use crate::error::PuzzleError;
+use crate::params::ALGORITHM;
+use crate::registry::Puzzle;
+use crate::runner::Run;
+use crate::solution::Answer;
+
+/// A part of a generated input on which a day's algorithms gave different answers.
+#[derive(Debug, Clone)]
+pub struct Disagreement {
+    /// The seed the input was generated from.
+    pub seed: u64,
+    pub part: u8,
+    /// Each algorithm's answer, or the error it failed with.
+    pub answers: Vec<(&'static str, Result<Answer, String>)>,
+}
+
+/// The result of solving generated inputs with each of a day's algorithms.
+#[derive(Debug, Clone)]
+pub struct Crosscheck {
+    /// How many inputs were solved.
+    pub inputs: usize,
+    /// How many inputs were left out because an algorithm ran past the timeout.
+    pub timed_out: usize,
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// Generates an input from each of `seeds`, solves both parts with every one of the
+/// puzzle's [`algorithms`](Puzzle::algorithms), and collects the parts where they do
+/// not all give the same answer. An algorithm that fails, or fails differently,
+/// disagrees with the others too, unless it gave up after `timeout`, in which case
+/// the input is left out.
+pub fn crosscheck(puzzle: &Puzzle, seeds: Range<u64>, size: Option<usize>, timeout: Option<Duration>) -> Crosscheck {
+    let mut inputs = 0;
+    let mut timed_out = 0;
+    let mut disagreements = Vec::new();
+
+    for seed in seeds {
+        let input = puzzle.generator.generate(seed, size);
+        let runs: Vec<_> = puzzle.algorithms().iter()
+            .map(|&algorithm| {
+                let mut params = puzzle.params();
+                params.set(ALGORITHM, algorithm).expect("the algorithm is declared");
+                (algorithm, cancel::with_timeout(timeout, || puzzle.run_with(input.as_bytes(), None, &params)))
+            })
+            .collect();
+        // Each part is solved separately, so either of them may have run out of time
+        let gave_up = |run: &Result<Run, PuzzleError>| match run {
+            Ok(run) => matches!(run.error(), Some(PuzzleError::TimedOut(_))),
+            Err(e) => matches!(e, PuzzleError::TimedOut(_)),
+        };
+        if runs.iter().any(|(_, run)| gave_up(run)) {
+            timed_out += 1;
+            continue;
+        }
+        inputs += 1;
+
+        for part in [1, 2] {
+            let answers: Vec<(&'static str, Result<Answer, String>)> = runs.iter()
+                .map(|(algorithm, run)| {
+                    let answer = match run {
+                        Ok(run) => {
+                            let part_run = if part == 1 { &run.part1 } else { &run.part2 };
+                            match part_run.as_ref().expect("both parts were solved") {
+                                Ok(part_run) => Ok(part_run.answer.clone()),
+                                Err(e) => Err(e.to_string()),
+                            }
+                        },
+                        Err(e) => Err(e.to_string()),
+                    };
+                    (*algorithm, answer)
+                })
+                .collect();
+            if answers.windows(2).any(|pair| pair[0].1 != pair[1].1) {
+                disagreements.push(Disagreement { seed, part, answers });
+            }
+        }
+    }
+
+    Crosscheck { inputs, timed_out, disagreements }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn test_algorithms_agree() {
+        for puzzle in registry::all().filter(|puzzle| puzzle.algorithms().len() > 1) {
+            let crosscheck = crosscheck(puzzle, 0..2, Some(6), None);
+            assert_eq!(2, crosscheck.inputs);
+            assert!(crosscheck.disagreements.is_empty(), "day {}: {:?}", puzzle.day, crosscheck.disagreements);
+        }
+    }
+
+    #[test]
+    fn test_timed_out_inputs_are_left_out() {
+        // A-star takes far too long on day 19's full-sized molecules
+        let puzzle = registry::get(19).unwrap();
+        let crosscheck = crosscheck(puzzle, 0..2, None, Some(Duration::ZERO));
+        assert_eq!((0, 2), (crosscheck.inputs, crosscheck.timed_out));
+        assert!(crosscheck.disagreements.is_empty());
+    }
+}
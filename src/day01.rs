@@ -1,37 +1,127 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    let mut instructions = String::new();
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ self, Answer, Query, QueryError, Solution };
 
-    for line in input {
-        instructions += &line.unwrap();
+pub struct Day01;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 1,
+    title: "Not Quite Lisp",
+    tags: &[Tag::Strings],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of instructions", default_size: 7000, generate },
+    solver: &Solver::<Day01>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "(())", expected: "0", params: &[] },
+    Example { part: 1, input: "()()", expected: "0", params: &[] },
+    Example { part: 1, input: "(((", expected: "3", params: &[] },
+    Example { part: 1, input: "(()(()(", expected: "3", params: &[] },
+    Example { part: 1, input: "))(((((", expected: "3", params: &[] },
+    Example { part: 1, input: "())", expected: "-1", params: &[] },
+    Example { part: 1, input: "))(", expected: "-1", params: &[] },
+    Example { part: 1, input: ")))", expected: "-3", params: &[] },
+    Example { part: 1, input: ")())())", expected: "-3", params: &[] },
+    Example { part: 2, input: ")", expected: "1", params: &[] },
+    Example { part: 2, input: "()())", expected: "5", params: &[] },
+];
+
+impl Solution for Day01 {
+    type Input = String;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<String> {
+        let mut instructions = String::new();
+
+        for line in input {
+            instructions += &line?;
+        }
+
+        Ok(instructions.replace('\n', ""))
     }
 
-    let instructions = instructions.replace("\n", "");
+    fn part1(instructions: &String, _params: &Params) -> Result<Answer> {
+        let mut floor = 0;
+        for c in instructions.chars() {
+            if c == '(' {
+                floor += 1;
+            } else if c == ')' {
+                floor -= 1;
+            }
+        }
 
-    let mut floor = 0;
-    for c in instructions.chars() {
-        if c == '(' {
-            floor += 1;
-        } else if c == ')' {
-            floor -= 1;
+        Ok(floor.into())
+    }
+
+    fn part2(instructions: &String, _params: &Params) -> Result<Answer> {
+        let mut floor = 0;
+        for (i, c) in instructions.chars().enumerate() {
+            if c == '(' {
+                floor += 1;
+            } else if c == ')' {
+                floor -= 1;
+            }
+
+            if floor == -1 {
+                return Ok((i+1).into());
+            }
         }
+
+        Err(PuzzleError::unsolvable("Santa never enters the basement"))
     }
 
-    println!("Part 1: Santa will end up at floor {}", floor);
+    const QUERIES: &'static [Query] = &[
+        Query { name: "floor-at", args: "<instructions>", help: "The floor Santa is on after this many instructions" },
+    ];
+
+    fn query(instructions: &String, _params: &Params, name: &str, args: &[&str]) -> std::result::Result<String, QueryError> {
+        match name {
+            "floor-at" => {
+                let count: usize = solution::query_argument(args, 0, "number of instructions")?;
+                if count > instructions.len() {
+                    return Err(QueryError::Usage(format!("there are only {} instructions", instructions.len())));
+                }
+                let floor: i64 = instructions.chars()
+                    .take(count)
+                    .map(|c| match c {
+                        '(' => 1,
+                        ')' => -1,
+                        _ => 0,
+                    })
+                    .sum();
+                Ok(format!("Floor {} after {} instructions", floor, count))
+            },
+            _ => Err(QueryError::UnknownCommand(name.to_string())),
+        }
+    }
+}
 
-    floor = 0;
-    for (i, c) in instructions.chars().enumerate() {
-        if c == '(' {
+/// Random moves, with enough extra `)` at the end to reach the basement if the
+/// moves never do, since part 2 needs it.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::with_capacity(size + 1);
+    let mut floor: i64 = 0;
+    let mut basement = false;
+    for _ in 0..size {
+        if rng.chance(1, 2) {
+            input.push('(');
             floor += 1;
-        } else if c == ')' {
+        } else {
+            input.push(')');
             floor -= 1;
         }
-
-        if floor == -1 {
-            println!("Part 2: Santa will enter the basement at position: {}", i+1);
-            break;
+        basement |= floor < 0;
+    }
+    if !basement {
+        for _ in 0..=floor {
+            input.push(')');
         }
     }
+    input
 }
@@ -1,8 +1,34 @@
 use std::cmp::min;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 
-struct Box {
+use crate::error::{ self, PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day02;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 2,
+    title: "I Was Told There Would Be No Math",
+    tags: &[Tag::Math],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of presents", default_size: 1000, generate },
+    solver: &Solver::<Day02>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "2x3x4", expected: "58", params: &[] },
+    Example { part: 1, input: "1x1x10", expected: "43", params: &[] },
+    Example { part: 2, input: "2x3x4", expected: "34", params: &[] },
+    Example { part: 2, input: "1x1x10", expected: "14", params: &[] },
+];
+
+#[derive(Debug)]
+pub struct Box {
     width: u32,
     height: u32,
     depth: u32,
@@ -14,7 +40,7 @@ impl Box {
         let b = self.width * self.depth;
         let c = self.height * self.depth;
         
-        return 2*a + 2*b + 2*c + min(min(a, b), c);
+        2*a + 2*b + 2*c + min(min(a, b), c)
     }
 
     fn ribbon_required(&self) -> u32 {
@@ -25,31 +51,50 @@ impl Box {
         let box_ribbon = min(a, min(b, c));
         let volume = self.width * self.height * self.depth;
 
-        return box_ribbon + volume;
+        box_ribbon + volume
     }
 
-    fn from_dimensions(dimensions: String) -> Self {
-        let dims = dimensions.split("x");
-        let dims: Vec<u32> = dims.map(|x| x.parse::<u32>().unwrap()).collect();
-        return Box {
+    fn from_dimensions(line_number: usize, dimensions: &str) -> Result<Self> {
+        let dims: Vec<&str> = dimensions.split('x').collect();
+        if dims.len() != 3 {
+            return Err(PuzzleError::invalid_line(line_number, dimensions, "expected dimensions like 2x3x4"));
+        }
+        let dims = dims.iter()
+            .map(|x| error::parse_number::<u32>(line_number, dimensions, x))
+            .collect::<Result<Vec<u32>>>()?;
+        Ok(Box {
             width: dims[0],
             height: dims[1],
             depth: dims[2],
-        }
+        })
     }
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    let mut total_wrapping_paper_size = 0;
-    let mut total_ribbon = 0;
+impl Solution for Day02 {
+    type Input = Vec<Box>;
 
-    for line in input {
-        let b = Box::from_dimensions(line.unwrap());
-        total_wrapping_paper_size += b.wrapping_paper_required();
-        total_ribbon += b.ribbon_required();
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<Box>> {
+        let mut boxes = Vec::new();
+        for (i, line) in input.enumerate() {
+            boxes.push(Box::from_dimensions(i+1, &line?)?);
+        }
+        Ok(boxes)
+    }
+
+    fn part1(boxes: &Vec<Box>, _params: &Params) -> Result<Answer> {
+        let total_wrapping_paper_size: u32 = boxes.iter().map(|b| b.wrapping_paper_required()).sum();
+        Ok(total_wrapping_paper_size.into())
     }
 
-    println!("Part 1: Wrapping paper required: {}", total_wrapping_paper_size);
-    println!("Part 2; Ribbon required: {}", total_ribbon);
+    fn part2(boxes: &Vec<Box>, _params: &Params) -> Result<Answer> {
+        let total_ribbon: u32 = boxes.iter().map(|b| b.ribbon_required()).sum();
+        Ok(total_ribbon.into())
+    }
+}
 
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{}x{}x{}", rng.range(1..=30), rng.range(1..=30), rng.range(1..=30)))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
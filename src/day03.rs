@@ -1,64 +1,79 @@
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    
-    let mut instructions = String::new();
-    for line in input {
-        instructions += &line.unwrap();
-    }
-    
-    let instructions = instructions.replace("\n", "");
-    
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::grid::{ Direction, Point };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day03;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 3,
+    title: "Perfectly Spherical Houses in a Vacuum",
+    tags: &[Tag::Grid, Tag::Simulation],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of moves", default_size: 8192, generate },
+    solver: &Solver::<Day03>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: ">", expected: "2", params: &[] },
+    Example { part: 1, input: "^>v<", expected: "4", params: &[] },
+    Example { part: 1, input: "^v^v^v^v^v", expected: "2", params: &[] },
+    Example { part: 2, input: "^v", expected: "3", params: &[] },
+    Example { part: 2, input: "^>v<", expected: "3", params: &[] },
+    Example { part: 2, input: "^v^v^v^v^v", expected: "11", params: &[] },
+];
 
-    // Part 1
-    let mut locations = HashSet::new();
-    let mut current_location = (0, 0);
-    locations.insert(current_location);
+impl Solution for Day03 {
+    type Input = String;
 
-    for c in instructions.chars() {
-        match c {
-            '<' => current_location = (current_location.0, current_location.1-1),
-            '^' => current_location = (current_location.0-1, current_location.1),
-            'v' => current_location = (current_location.0+1, current_location.1),
-            '>' => current_location = (current_location.0, current_location.1+1),
-            _ => {},
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<String> {
+        let mut instructions = String::new();
+        for line in input {
+            instructions += &line?;
         }
-        locations.insert(current_location);
+
+        Ok(instructions.replace('\n', ""))
     }
 
-    println!("Part 1: Santa will visit {} unique houses", locations.len());
+    fn part1(instructions: &String, _params: &Params) -> Result<Answer> {
+        let mut locations = HashSet::new();
+        let mut current_location = Point::ORIGIN;
+        locations.insert(current_location);
+
+        for direction in instructions.chars().filter_map(Direction::from_arrow) {
+            current_location = current_location.step(direction);
+            locations.insert(current_location);
+        }
 
-    // Part 2
-    let mut locations = HashSet::new();
-    let mut santa = (0, 0);
-    let mut robot = (0, 0);
-    locations.insert(santa);
+        Ok(locations.len().into())
+    }
 
-    for (i, c) in instructions.chars().enumerate() {
-        if i % 2 == 0 {
-            match c {
-                '<' => santa = (santa.0, santa.1-1),
-                '^' => santa = (santa.0-1, santa.1),
-                'v' => santa = (santa.0+1, santa.1),
-                '>' => santa = (santa.0, santa.1+1),
-                _ => {},
-            }
-            locations.insert(santa);
+    fn part2(instructions: &String, _params: &Params) -> Result<Answer> {
+        let mut locations = HashSet::new();
+        let mut santa = Point::ORIGIN;
+        let mut robot = Point::ORIGIN;
+        locations.insert(santa);
 
-        } else {
-            match c {
-                '<' => robot = (robot.0, robot.1-1),
-                '^' => robot = (robot.0-1, robot.1),
-                'v' => robot = (robot.0+1, robot.1),
-                '>' => robot = (robot.0, robot.1+1),
-                _ => {},
+        // Santa and the robot take turns, even over characters that are not moves
+        for (i, c) in instructions.chars().enumerate() {
+            let mover = if i % 2 == 0 { &mut santa } else { &mut robot };
+            if let Some(direction) = Direction::from_arrow(c) {
+                *mover = mover.step(direction);
             }
-            locations.insert(robot);
+            locations.insert(*mover);
         }
-    }
 
-    println!("Part 2: Santa and the robot will visit {} unique houses", locations.len());
+        Ok(locations.len().into())
+    }
+}
 
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| *rng.choose(&['^', 'v', '<', '>'])).collect()
 }
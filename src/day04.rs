@@ -1,47 +1,80 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-use md5;
+use std::io::{ BufRead, Lines };
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    let mut secret_key = String::new();
+use crate::cancel;
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
 
-    for line in input {
-        secret_key += &line.unwrap();
-    }
+pub struct Day04;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 4,
+    title: "The Ideal Stocking Stuffer",
+    tags: &[Tag::Hashing, Tag::BruteForce],
+    examples: EXAMPLES,
+    generator: Generator { size: "length of the secret key", default_size: 8, generate },
+    solver: &Solver::<Day04>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "abcdef", expected: "609043", params: &[] },
+    Example { part: 1, input: "pqrstuv", expected: "1048970", params: &[] },
+];
+
+impl Solution for Day04 {
+    type Input = String;
 
-    let secret_key = secret_key.replace("\n", "");
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<String> {
+        let mut secret_key = String::new();
 
-    let mut i = 0;
-    loop {
-        if hash_starts_with_five_zeroes(&secret_key, i) {
-            println!("Part 1: The lowest number to yield a good hash is: {i}");
-            break;
+        for line in input {
+            secret_key += &line?;
         }
-        i += 1;
+
+        Ok(secret_key.replace('\n', ""))
     }
 
+    fn part1(secret_key: &String, _params: &Params) -> Result<Answer> {
+        let mut i = 0;
+        loop {
+            cancel::check()?;
+            if hash_starts_with_five_zeroes(secret_key, i) {
+                return Ok(i.into());
+            }
+            i += 1;
+        }
+    }
 
-    i = 0;
-    loop {
-        if hash_starts_with_six_zeroes(&secret_key, i) {
-            println!("Part 2: The lowest number to yield a good hash is: {i}");
-            break;
+    fn part2(secret_key: &String, _params: &Params) -> Result<Answer> {
+        let mut i = 0;
+        loop {
+            cancel::check()?;
+            if hash_starts_with_six_zeroes(secret_key, i) {
+                return Ok(i.into());
+            }
+            i += 1;
         }
-        i += 1;
     }
 }
 
-fn hash_starts_with_five_zeroes(secret_key: &String, num: u32) -> bool {
+fn hash_starts_with_five_zeroes(secret_key: &str, num: u32) -> bool {
     let input = format!("{secret_key}{num}");
     let digest = md5::compute(input.as_bytes());
     let digest = &digest[..3];
-    return digest[0] == 0 && digest[1] == 0 && digest[2] <= 16;
+    digest[0] == 0 && digest[1] == 0 && digest[2] <= 16
 }
 
-fn hash_starts_with_six_zeroes(secret_key: &String, num: u32) -> bool {
+fn hash_starts_with_six_zeroes(secret_key: &str, num: u32) -> bool {
     let input = format!("{secret_key}{num}");
     let digest = md5::compute(input.as_bytes());
     let digest = &digest[..3];
-    return digest[0] == 0 && digest[1] == 0 && digest[2] == 0;
+    digest[0] == 0 && digest[1] == 0 && digest[2] == 0
 }
 
+fn generate(rng: &mut Rng, size: usize) -> String {
+    rng.letters(size.max(1))
+}
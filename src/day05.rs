@@ -1,25 +1,51 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 use std::collections::HashMap;
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    let mut nice_strings_part_1: u32 = 0;
-    let mut nice_strings_part_2: u32 = 0;
-
-    for line in input {
-        let slice = &line.unwrap()[..];
-
-        if is_nice_string_part_1(&slice) {
-            nice_strings_part_1 += 1;
-        }
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day05;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 5,
+    title: "Doesn't He Have Intern-Elves For This?",
+    tags: &[Tag::Strings],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of strings", default_size: 1000, generate },
+    solver: &Solver::<Day05>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "ugknbfddgicrmopn", expected: "1", params: &[] },
+    Example { part: 1, input: "aaa", expected: "1", params: &[] },
+    Example { part: 1, input: "jchzalrnumimnmhp", expected: "0", params: &[] },
+    Example { part: 1, input: "haegwjzuvuyypxyu", expected: "0", params: &[] },
+    Example { part: 1, input: "dvszwmarrgswjxmb", expected: "0", params: &[] },
+    Example { part: 2, input: "qjhvhtzxzqqjkmpb", expected: "1", params: &[] },
+    Example { part: 2, input: "xxyxx", expected: "1", params: &[] },
+    Example { part: 2, input: "uurcxstgmygtbstg", expected: "0", params: &[] },
+    Example { part: 2, input: "ieodomkazucvgmuy", expected: "0", params: &[] },
+];
+
+impl Solution for Day05 {
+    type Input = Vec<String>;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<String>> {
+        Ok(input.collect::<std::io::Result<Vec<String>>>()?)
+    }
 
-        if is_nice_string_part_2(&slice) {
-            nice_strings_part_2 += 1;
-        }
+    fn part1(strings: &Vec<String>, _params: &Params) -> Result<Answer> {
+        Ok(strings.iter().filter(|s| is_nice_string_part_1(s)).count().into())
     }
 
-    println!("Part 1: Total number of nice strings: {}", nice_strings_part_1);
-    println!("Part 2: Total number of nice strings: {}", nice_strings_part_2);
+    fn part2(strings: &Vec<String>, _params: &Params) -> Result<Answer> {
+        Ok(strings.iter().filter(|s| is_nice_string_part_2(s)).count().into())
+    }
 }
 
 fn is_nice_string_part_1(s: &str) -> bool {
@@ -96,4 +122,8 @@ fn is_nice_string_part_2(s: &str) -> bool {
     }
 
     false
-}
\ No newline at end of file
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| rng.letters(16)).collect::<Vec<_>>().join("\n")
+}
@@ -1,11 +1,37 @@
-use lazy_static::lazy_static;
 use std::fmt;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-use regex::Regex;
-
-#[derive(Debug)] 
-enum Operation {
+use std::io::{ BufRead, Lines };
+
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::grid::{ Grid, Point, Rect };
+use crate::params::Params;
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution, Visual };
+
+pub struct Day06;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 6,
+    title: "Probably a Fire Hazard",
+    tags: &[Tag::Grid, Tag::Simulation],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of instructions", default_size: 300, generate },
+    solver: &Solver::<Day06>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "turn on 0,0 through 999,999", expected: "1000000", params: &[] },
+    Example { part: 1, input: "toggle 0,0 through 999,0", expected: "1000", params: &[] },
+    Example { part: 1, input: "turn on 0,0 through 999,999\nturn off 499,499 through 500,500", expected: "999996", params: &[] },
+    Example { part: 2, input: "turn on 0,0 through 0,0", expected: "1", params: &[] },
+    Example { part: 2, input: "toggle 0,0 through 999,999", expected: "2000000", params: &[] },
+];
+
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
     TurnOn,
     TurnOff,
     Toggle,
@@ -17,152 +43,161 @@ impl fmt::Display for Operation {
     }
 }
 
-struct Instruction {
-    start_x: usize,
-    start_y: usize,
-    end_x: usize,
-    end_y: usize,
+#[derive(Debug)]
+pub struct Instruction {
+    area: Rect,
     operation: Operation,
 }
 
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} : {},{} -> {},{}", self.operation, self.start_x, self.start_y, self.end_x, self.end_y)
+        write!(f, "{} : {} -> {}", self.operation, self.area.min, self.area.max)
     }
 }
 
+const INSTRUCTIONS: [(Operation, Pattern); 3] = [
+    (Operation::TurnOn, Pattern::new("turn on {x1},{y1} through {x2},{y2}")),
+    (Operation::TurnOff, Pattern::new("turn off {x1},{y1} through {x2},{y2}")),
+    (Operation::Toggle, Pattern::new("toggle {x1},{y1} through {x2},{y2}")),
+];
+
 impl Instruction {
-    fn from_text(s: &String) -> Self {
-        let mut start_x = 0;
-        let mut start_y = 0;
-        let mut end_x = 0;
-        let mut end_y = 0;
-        let mut operation = Operation::Toggle;
-
-        if s.starts_with("turn on") {
-            operation = Operation::TurnOn;
-        } else if s.starts_with("turn off") {
-            operation = Operation::TurnOff;
+    fn from_text(line_number: usize, s: &str) -> Result<Self> {
+        let Some(&(operation, pattern)) = INSTRUCTIONS.iter().find(|(_, pattern)| s.starts_with(pattern.prefix())) else {
+            return Err(PuzzleError::invalid_line(line_number, s, "expected 'turn on', 'turn off' or 'toggle'"));
+        };
+        let fields = pattern.parse(line_number, s)?;
+
+        let mut coordinates = [0; 4];
+        for (value, name) in coordinates.iter_mut().zip(["x1", "y1", "x2", "y2"]) {
+            *value = fields.number(name)?;
+            if !(0..1000).contains(value) {
+                return Err(fields.invalid(name, "coordinates must be between 0 and 999"));
+            }
         }
 
-        lazy_static! {
-            static ref RE: Regex = Regex::new("(\\d+)").unwrap();
-        }
+        let instruction = Instruction {
+            area: Rect::new(Point::new(coordinates[0], coordinates[1]), Point::new(coordinates[2], coordinates[3])),
+            operation,
+        };
 
-        for (i, cap) in RE.captures_iter(s).enumerate() {
-            match i {
-                0 => start_x = (&cap[1]).parse().unwrap(),
-                1 => start_y = (&cap[1]).parse().unwrap(),
-                2 => end_x = (&cap[1]).parse().unwrap(),
-                3 => end_y = (&cap[1]).parse().unwrap(),
-                _ => panic!(),
-            }
+        if instruction.area.is_empty() {
+            return Err(PuzzleError::invalid_line(line_number, s, "the first corner must be above and to the left of the second"));
         }
 
-        return Instruction {
-            start_x: start_x,
-            start_y: start_y,
-            end_x: end_x,
-            end_y: end_y,
-            operation: operation,
-        }
+        Ok(instruction)
     }
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+type Lights<T> = Grid<T>;
 
-    let mut part_1_lights: [[bool; 1000]; 1000] = [[false; 1000]; 1000];
-    let mut part_2_lights: [[u32; 1000]; 1000] = [[0; 1000]; 1000];
+impl Solution for Day06 {
+    type Input = Vec<Instruction>;
 
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<Instruction>> {
+        let mut instructions = Vec::new();
+        for (i, line) in input.enumerate() {
+            instructions.push(Instruction::from_text(i+1, &line?)?);
+        }
+        Ok(instructions)
+    }
 
-    for line in input {
-        let instr = Instruction::from_text(&line.unwrap());
-        update_part_1_lights(&mut part_1_lights, &instr);
-        update_part_2_lights(&mut part_2_lights, &instr);
+    fn part1(instructions: &Vec<Instruction>, _params: &Params) -> Result<Answer> {
+        Ok(count_lights(&part_1_lights(instructions)).into())
     }
 
-    println!("Part 1: Total number of lit lights: {}", count_lights(&part_1_lights));
-    println!("Part 2: Total brightness: {}", calculate_brightness(&part_2_lights));
-}
+    fn part2(instructions: &Vec<Instruction>, _params: &Params) -> Result<Answer> {
+        Ok(calculate_brightness(&part_2_lights(instructions)).into())
+    }
 
-fn count_lights(lights: &[[bool; 1000]; 1000]) -> u32 {
-    let mut count = 0;
+    fn visualize(instructions: &Vec<Instruction>, _params: &Params) -> Result<Vec<Visual>> {
+        Ok(vec![
+            Visual::new("Lights after part 1", shade(&part_1_lights(instructions), |&light| u32::from(light))),
+            Visual::new("Brightness after part 2", shade(&part_2_lights(instructions), |&brightness| brightness)),
+        ])
+    }
+}
 
-    for y in 0..1000 {
-        for x in 0..1000 {
-            if lights[y][x] {
-                count += 1;
-            }
-        }
+fn part_1_lights(instructions: &[Instruction]) -> Lights<bool> {
+    let mut lights: Lights<bool> = Grid::new(1000, 1000, false);
+    for instr in instructions {
+        update_part_1_lights(&mut lights, instr);
     }
+    lights
+}
 
-    return count;
+fn part_2_lights(instructions: &[Instruction]) -> Lights<u32> {
+    let mut lights: Lights<u32> = Grid::new(1000, 1000, 0);
+    for instr in instructions {
+        update_part_2_lights(&mut lights, instr);
+    }
+    lights
 }
 
-fn calculate_brightness(lights: &[[u32; 1000]; 1000]) -> u32 {
-    let mut brightness = 0;
+/// Draws the grid with one character for every 10x20 block of lights (characters
+/// are about twice as tall as they are wide), shaded by the total `value` of the
+/// lights in the block compared with the brightest block.
+fn shade<T>(lights: &Lights<T>, value: fn(&T) -> u32) -> String {
+    const SHADES: &[u8] = b" .:-=+*#%@";
+    const BLOCK_WIDTH: usize = 10;
+    const BLOCK_HEIGHT: usize = 20;
+
+    let rows: Vec<&[T]> = lights.rows().collect();
+    let totals: Vec<Vec<u64>> = rows.chunks(BLOCK_HEIGHT)
+        .map(|rows| (0..lights.width() / BLOCK_WIDTH)
+            .map(|col| rows.iter()
+                .flat_map(|row| &row[col * BLOCK_WIDTH..(col + 1) * BLOCK_WIDTH])
+                .map(|light| u64::from(value(light)))
+                .sum())
+            .collect())
+        .collect();
+    let brightest = totals.iter().flatten().copied().max().unwrap_or(0).max(1);
+    let levels = (SHADES.len() - 1) as u64;
+
+    totals.iter()
+        .map(|row| row.iter()
+            // Round up, so that only blocks with every light off are blank
+            .map(|&total| SHADES[((total * levels).div_ceil(brightest)) as usize] as char)
+            .collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
 
-    for y in 0..1000 {
-        for x in 0..1000 {
-            brightness += lights[y][x]
-        }
-    }
+fn count_lights(lights: &Lights<bool>) -> u32 {
+    lights.values().filter(|&&light| light).count() as u32
+}
 
-    return brightness;
+fn calculate_brightness(lights: &Lights<u32>) -> u32 {
+    lights.values().sum()
 }
 
-fn update_part_1_lights(lights: &mut [[bool; 1000]; 1000], instr: &Instruction) {
+fn update_part_1_lights(lights: &mut Lights<bool>, instr: &Instruction) {
+    let area = lights.rect_mut(instr.area);
 
     match instr.operation {
-        Operation::TurnOn => {
-            for y in instr.start_y..instr.end_y+1 {
-                for x in instr.start_x..instr.end_x+1 {
-                    lights[y][x] = true;
-                }
-            }
-        },
-        Operation::TurnOff => {
-            for y in instr.start_y..instr.end_y+1 {
-                for x in instr.start_x..instr.end_x+1 {
-                    lights[y][x] = false;
-                }
-            }
-        },
-        Operation::Toggle => {
-            for y in instr.start_y..instr.end_y+1 {
-                for x in instr.start_x..instr.end_x+1 {
-                    lights[y][x] = !lights[y][x];
-                }
-            }
-        },
+        Operation::TurnOn => area.for_each(|light| *light = true),
+        Operation::TurnOff => area.for_each(|light| *light = false),
+        Operation::Toggle => area.for_each(|light| *light = !*light),
     }
 }
 
-fn update_part_2_lights(lights: &mut [[u32; 1000]; 1000], instr: &Instruction) {
+fn update_part_2_lights(lights: &mut Lights<u32>, instr: &Instruction) {
+    let area = lights.rect_mut(instr.area);
 
     match instr.operation {
-        Operation::TurnOn => {
-            for y in instr.start_y..instr.end_y+1 {
-                for x in instr.start_x..instr.end_x+1 {
-                    lights[y][x] += 1;
-                }
-            }
-        },
-        Operation::TurnOff => {
-            for y in instr.start_y..instr.end_y+1 {
-                for x in instr.start_x..instr.end_x+1 {
-                    if lights[y][x] > 0 {
-                        lights[y][x] -= 1;
-                    }
-                }
-            }
-        },
-        Operation::Toggle => {
-            for y in instr.start_y..instr.end_y+1 {
-                for x in instr.start_x..instr.end_x+1 {
-                    lights[y][x] += 2;
-                }
-            }
-        },
+        Operation::TurnOn => area.for_each(|light| *light += 1),
+        Operation::TurnOff => area.for_each(|light| *light = light.saturating_sub(1)),
+        Operation::Toggle => area.for_each(|light| *light += 2),
+    }
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let action = rng.choose(&["turn on", "turn off", "toggle"]);
+        let (x1, y1) = (rng.range(0..=999), rng.range(0..=999));
+        let (x2, y2) = (rng.range(x1..=999), rng.range(y1..=999));
+        lines.push(format!("{} {},{} through {},{}", action, x1, y1, x2, y2));
     }
+    lines.join("\n")
 }
@@ -1,15 +1,32 @@
 use std::fmt;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-use std::collections::HashMap;
+use std::io::{ BufRead, Lines };
+use std::collections::{ HashMap, HashSet };
+
+use crate::error::{ PuzzleError, Result };
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ self, Answer, Query, QueryError, Solution };
+
+pub struct Day07;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 7,
+    title: "Some Assembly Required",
+    tags: &[Tag::Graph, Tag::Simulation],
+    examples: &[],
+    generator: Generator { size: "number of wires", default_size: 339, generate },
+    solver: &Solver::<Day07>::NEW,
+};
 
 #[derive(Debug)] 
 enum LogicGate {
-    AND,
-    OR,
-    NOT,
-    LSHIFT,
-    RSHIFT,
+    And,
+    Or,
+    Not,
+    Lshift,
+    Rshift,
 }
 
 impl fmt::Display for LogicGate {
@@ -18,121 +35,292 @@ impl fmt::Display for LogicGate {
     }
 }
 
+/// An input to a gate: a signal given directly, or the signal on another wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Signal(u16),
+    Wire(String),
+}
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+/// What drives a wire. Shifts are always by a number of bits given directly,
+/// below 16.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Value(Source),
+    And(Source, Source),
+    Or(Source, Source),
+    Lshift(Source, u16),
+    Rshift(Source, u16),
+    Not(Source),
+}
 
-    let mut circuit = HashMap::new();
-    let mut signals = HashMap::new();
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Signal(v) => write!(f, "{}", v),
+            Source::Wire(wire) => write!(f, "{}", wire),
+        }
+    }
+}
 
-    for line in input {
-        read_instruction(line.unwrap(), &mut circuit);
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Value(x) => write!(f, "{}", x),
+            Instruction::And(x, y) => write!(f, "{} AND {}", x, y),
+            Instruction::Or(x, y) => write!(f, "{} OR {}", x, y),
+            Instruction::Lshift(x, k) => write!(f, "{} LSHIFT {}", x, k),
+            Instruction::Rshift(x, k) => write!(f, "{} RSHIFT {}", x, k),
+            Instruction::Not(x) => write!(f, "NOT {}", x),
+        }
     }
+}
 
-    let mut signals_clone = signals.clone();
-    let result = solve_for_wire(String::from("a"), &circuit, &mut signals_clone);
-    println!("Part 1: Final signal on wire a: {}", result);
+pub type Circuit = HashMap<String, Instruction>;
 
-    circuit.insert(String::from("b"), result.to_string());
-    let result = solve_for_wire(String::from("a"), &circuit, &mut signals);
-    println!("Part 2: Final signal on wire a: {}", result);
-}
+const CONNECTION: Pattern = Pattern::new("{instruction} -> {wire}");
+
+impl Solution for Day07 {
+    type Input = Circuit;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Circuit> {
+        let mut circuit = HashMap::new();
 
-fn solve_for_wire(wire: String, circuit: &HashMap<String, String>, signals: &mut HashMap<String, u16>) -> u16 {
-    // Early exit: If the wire is not a wire, but a raw signal
-    match wire.parse::<u16>() {
-        Ok(v) => {
-            return v;
-        },
-        Err(_) => {},
+        for (i, line) in input.enumerate() {
+            let (wire, instruction) = read_instruction(i+1, &line?)?;
+            circuit.insert(wire, instruction);
+        }
+
+        Ok(circuit)
     }
 
-    // Early exit: If the signal on this wire has already been worked out
-    if signals.contains_key(&wire) {
-        return *signals.get(&wire).unwrap();
+    fn part1(circuit: &Circuit, _params: &Params) -> Result<Answer> {
+        Ok(Signals::new(circuit).solve_for_wire("a")?.into())
     }
 
-    let msg = format!("Circuit does not contain wire: '{}'", wire);
-    let instruction = circuit.get(&wire).expect(&msg);
-    
-    // Early exit: If the signal on the wire is trivially assigned in the circuit specs
-    match instruction.parse::<u16>() {
-        Ok(v) => {
-            signals.insert(wire, v);
-            return v;
-        },
-        Err(_) => {},
+    fn part2(circuit: &Circuit, _params: &Params) -> Result<Answer> {
+        let result = Signals::new(circuit).solve_for_wire("a")?;
+
+        let mut circuit = circuit.clone();
+        circuit.insert(String::from("b"), Instruction::Value(Source::Signal(result)));
+        Ok(Signals::new(&circuit).solve_for_wire("a")?.into())
     }
-    
-    // Hard work: solve a logic gate
-    let value: u16;
-    if instruction.contains("AND") {
-        let split: Vec<&str> = instruction.split(" AND ").collect();
-        let lhs = split.get(0).unwrap().to_string();
-        let lhs = solve_for_wire(lhs, circuit, signals);
 
-        let rhs = split.get(1).unwrap().to_string();
-        let rhs = solve_for_wire(rhs, circuit, signals);
-        value = apply_gate(lhs, rhs, LogicGate::AND);
-        
-    } else if instruction.contains("OR") {
-        let split: Vec<&str> = instruction.split(" OR ").collect();
-        let lhs = split.get(0).unwrap().to_string();
-        let lhs = solve_for_wire(lhs, circuit, signals);
+    const QUERIES: &'static [Query] = &[
+        Query { name: "wire", args: "<name>", help: "The signal on a wire, and the instruction that drives it" },
+    ];
+
+    fn query(circuit: &Circuit, _params: &Params, name: &str, args: &[&str]) -> std::result::Result<String, QueryError> {
+        match name {
+            "wire" => {
+                let wire: String = solution::query_argument(args, 0, "wire name")?;
+                let instruction = circuit.get(&wire)
+                    .ok_or_else(|| QueryError::Usage(format!("there is no wire '{}'", wire)))?;
+                let signal = Signals::new(circuit).solve_for_wire(&wire)?;
+                Ok(format!("{} -> {} = {}", instruction, wire, signal))
+            },
+            _ => Err(QueryError::UnknownCommand(name.to_string())),
+        }
+    }
+}
 
-        let rhs = split.get(1).unwrap().to_string();
-        let rhs = solve_for_wire(rhs, circuit, signals);
-        value = apply_gate(lhs, rhs, LogicGate::OR);
-        
-    } else if instruction.contains("LSHIFT") {
-        let split: Vec<&str> = instruction.split(" LSHIFT ").collect();
-        let lhs = split.get(0).unwrap().to_string();
-        let lhs = solve_for_wire(lhs, circuit, signals);
+/// The signals worked out so far on the wires of a circuit.
+struct Signals<'a> {
+    circuit: &'a Circuit,
+    known: HashMap<&'a str, u16>,
+    /// The wires whose signal is being worked out, to catch loops in the circuit
+    in_progress: HashSet<&'a str>,
+}
 
-        let rhs = split.get(1).unwrap().to_string();
-        let rhs = solve_for_wire(rhs, circuit, signals);
-        value = apply_gate(lhs, rhs, LogicGate::LSHIFT);
+impl<'a> Signals<'a> {
+    fn new(circuit: &'a Circuit) -> Self {
+        Signals { circuit, known: HashMap::new(), in_progress: HashSet::new() }
+    }
 
-    } else if instruction.contains("RSHIFT") {
-        let split: Vec<&str> = instruction.split(" RSHIFT ").collect();
-        let lhs = split.get(0).unwrap().to_string();
-        let lhs = solve_for_wire(lhs, circuit, signals);
+    fn solve_for_wire(&mut self, wire: &str) -> Result<u16> {
+        // Early exit: If the signal on this wire has already been worked out
+        if let Some(v) = self.known.get(wire) {
+            return Ok(*v);
+        }
+
+        let (wire, instruction) = self.circuit.get_key_value(wire)
+            .ok_or_else(|| PuzzleError::unsolvable(format!("Circuit does not contain wire: '{}'", wire)))?;
+        if !self.in_progress.insert(wire) {
+            return Err(PuzzleError::unsolvable(format!("The signal on wire '{}' depends on itself", wire)));
+        }
+
+        let value = match instruction {
+            Instruction::Value(x) => self.solve(x)?,
+            Instruction::And(x, y) => apply_gate(self.solve(x)?, self.solve(y)?, LogicGate::And),
+            Instruction::Or(x, y) => apply_gate(self.solve(x)?, self.solve(y)?, LogicGate::Or),
+            Instruction::Lshift(x, k) => apply_gate(self.solve(x)?, *k, LogicGate::Lshift),
+            Instruction::Rshift(x, k) => apply_gate(self.solve(x)?, *k, LogicGate::Rshift),
+            Instruction::Not(x) => apply_gate(self.solve(x)?, 0, LogicGate::Not),
+        };
+
+        self.in_progress.remove(wire.as_str());
+        self.known.insert(wire, value);
+        Ok(value)
+    }
 
-        let rhs = split.get(1).unwrap().to_string();
-        let rhs = solve_for_wire(rhs, circuit, signals);
-        value = apply_gate(lhs, rhs, LogicGate::RSHIFT);
+    fn solve(&mut self, source: &Source) -> Result<u16> {
+        match source {
+            Source::Signal(v) => Ok(*v),
+            Source::Wire(wire) => self.solve_for_wire(wire),
+        }
+    }
+}
 
-    } else if instruction.contains("NOT") {
-        let rhs = instruction[4..].to_string();
-        let rhs = solve_for_wire(rhs, circuit, signals);
-        value = apply_gate(rhs, 0, LogicGate::NOT);
 
-    } else {
-        // instruction is a single wire connected directly to the target wire
-        value = solve_for_wire(instruction.to_string(), circuit, signals);
+fn read_instruction(line_number: usize, line: &str) -> Result<(String, Instruction)> {
+    let fields = CONNECTION.parse(line_number, line)?;
+    let wire = fields.str("wire");
+    if !is_wire_name(wire) {
+        return Err(fields.invalid("wire", format!("expected a wire name, found '{}'", wire)));
     }
 
-    signals.insert(wire, value);
-    return value;
+    let source = |token: &str| {
+        match token.parse::<u16>() {
+            Ok(v) => Ok(Source::Signal(v)),
+            Err(_) if is_wire_name(token) => Ok(Source::Wire(token.to_string())),
+            Err(_) => Err(PuzzleError::invalid_token(line_number, line, token, format!("expected a signal or a wire name, found '{}'", token))),
+        }
+    };
+    let shift = |token: &str| {
+        match token.parse::<u16>() {
+            Ok(k) if k < 16 => Ok(k),
+            _ => Err(PuzzleError::invalid_token(line_number, line, token, format!("expected a shift of 0 to 15 bits, found '{}'", token))),
+        }
+    };
+
+    let words: Vec<&str> = fields.str("instruction").split(' ').collect();
+    let instruction = match words[..] {
+        [x] => Instruction::Value(source(x)?),
+        ["NOT", x] => Instruction::Not(source(x)?),
+        [x, "AND", y] => Instruction::And(source(x)?, source(y)?),
+        [x, "OR", y] => Instruction::Or(source(x)?, source(y)?),
+        [x, "LSHIFT", k] => Instruction::Lshift(source(x)?, shift(k)?),
+        [x, "RSHIFT", k] => Instruction::Rshift(source(x)?, shift(k)?),
+        [_, gate, _] => return Err(PuzzleError::invalid_token(line_number, line, gate, format!("expected AND, OR, LSHIFT or RSHIFT, found '{}'", gate))),
+        _ => return Err(fields.invalid("instruction", "expected an instruction like 'x AND y'")),
+    };
+    Ok((wire.to_string(), instruction))
 }
 
-
-fn read_instruction(s: String, circuit: &mut HashMap<String, String>) {
-    let split: Vec<&str> = s.split(" -> ").collect();
-    let lhs = *split.get(0).unwrap();
-    let rhs = *split.get(1).unwrap();
-    circuit.insert(String::from(rhs), String::from(lhs));
+fn is_wire_name(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|c| c.is_ascii_lowercase())
 }
 
 fn apply_gate(a: u16, b: u16, gate: LogicGate) -> u16 {
     match gate {
-        LogicGate::AND => a & b,
-        LogicGate::OR => a | b,
-        LogicGate::NOT => !a,
-        LogicGate::LSHIFT => a << b,
-        LogicGate::RSHIFT => a >> b,
+        LogicGate::And => a & b,
+        LogicGate::Or => a | b,
+        LogicGate::Not => !a,
+        LogicGate::Lshift => a << b,
+        LogicGate::Rshift => a >> b,
     }
 }
 
+/// A gate in a generated circuit, with its inputs given by the index of their wire.
+enum Gate {
+    Signal(u16),
+    And(usize, usize),
+    AndOne(usize),
+    Or(usize, usize),
+    Lshift(usize, u16),
+    Rshift(usize, u16),
+    Not(usize),
+    Wire(usize),
+}
+
+/// A random circuit, listed in a random order. Like in the real input, wire `b`
+/// is given a signal directly, so that part 2 can override it, and the signal on
+/// `a` depends on it, so that part 2 has a different answer.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    let signals = (size / 50).max(1);
+
+    let gates = loop {
+        let mut gates = Vec::with_capacity(size);
+        for i in 0..size {
+            let (x, y) = (rng.index(i.max(1)), rng.index(i.max(1)));
+            gates.push(match rng.index(7) {
+                _ if i < signals => Gate::Signal(rng.range(0..=65535) as u16),
+                0 => Gate::And(x, y),
+                1 => Gate::AndOne(x),
+                2 => Gate::Or(x, y),
+                3 => Gate::Lshift(x, rng.range(1..=15) as u16),
+                4 => Gate::Rshift(x, rng.range(1..=15) as u16),
+                5 => Gate::Not(x),
+                _ => Gate::Wire(x),
+            });
+        }
+
+        let Gate::Signal(b) = gates[0] else { unreachable!() };
+        let a = generated_signal(&gates, b);
+        if generated_signal(&gates, a) != a {
+            break gates;
+        }
+    };
+
+    // `b` is the first wire and `a` the last
+    let mut names = vec![String::from("b")];
+    names.extend((2..size).map(wire_name));
+    names.push(String::from("a"));
+
+    let mut lines: Vec<String> = gates.iter().zip(&names)
+        .map(|(gate, name)| {
+            let source = match *gate {
+                Gate::Signal(v) => v.to_string(),
+                Gate::And(x, y) => format!("{} AND {}", names[x], names[y]),
+                Gate::AndOne(x) => format!("1 AND {}", names[x]),
+                Gate::Or(x, y) => format!("{} OR {}", names[x], names[y]),
+                Gate::Lshift(x, k) => format!("{} LSHIFT {}", names[x], k),
+                Gate::Rshift(x, k) => format!("{} RSHIFT {}", names[x], k),
+                Gate::Not(x) => format!("NOT {}", names[x]),
+                Gate::Wire(x) => names[x].clone(),
+            };
+            format!("{} -> {}", source, name)
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
+
+/// The signal on the last wire of a generated circuit, with `b` set to the given signal.
+fn generated_signal(gates: &[Gate], b: u16) -> u16 {
+    let mut signals: Vec<u16> = Vec::with_capacity(gates.len());
+    for gate in gates {
+        let signal = match *gate {
+            _ if signals.is_empty() => b,
+            Gate::Signal(v) => v,
+            Gate::And(x, y) => apply_gate(signals[x], signals[y], LogicGate::And),
+            Gate::AndOne(x) => apply_gate(1, signals[x], LogicGate::And),
+            Gate::Or(x, y) => apply_gate(signals[x], signals[y], LogicGate::Or),
+            Gate::Lshift(x, k) => apply_gate(signals[x], k, LogicGate::Lshift),
+            Gate::Rshift(x, k) => apply_gate(signals[x], k, LogicGate::Rshift),
+            Gate::Not(x) => apply_gate(signals[x], 0, LogicGate::Not),
+            Gate::Wire(x) => signals[x],
+        };
+        signals.push(signal);
+    }
+    *signals.last().unwrap()
+}
+
+/// The `i`th wire name in the order a, b, ..., z, aa, ab, ...
+fn wire_name(mut i: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'a' + (i % 26) as u8);
+        i /= 26;
+        if i == 0 {
+            break;
+        }
+        i -= 1;
+    }
+    name.iter().rev().map(|&c| c as char).collect()
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -140,7 +328,7 @@ mod tests {
 
     #[test]
     fn apply_or() {
-        let gate = LogicGate::OR;
+        let gate = LogicGate::Or;
         let x: u16 = 123;
         let y: u16 = 456;
         let expected: u16 = 507;
@@ -151,7 +339,7 @@ mod tests {
 
     #[test]
     fn apply_and() {
-        let gate = LogicGate::AND;
+        let gate = LogicGate::And;
         let x: u16 = 123;
         let y: u16 = 456;
         let expected: u16 = 72;
@@ -162,7 +350,7 @@ mod tests {
 
     #[test]
     fn apply_lshift() {
-        let gate = LogicGate::LSHIFT;
+        let gate = LogicGate::Lshift;
         let x: u16 = 123;
         let y: u16 = 2;
         let expected: u16 = 492;
@@ -173,7 +361,7 @@ mod tests {
 
     #[test]
     fn apply_rshift() {
-        let gate = LogicGate::RSHIFT;
+        let gate = LogicGate::Rshift;
         let x: u16 = 456;
         let y: u16 = 2;
         let expected: u16 = 114;
@@ -184,11 +372,37 @@ mod tests {
 
     #[test]
     fn apply_not() {
-        let gate = LogicGate::NOT;
+        let gate = LogicGate::Not;
         let x: u16 = 123;
         let expected: u16 = 65412;
         let actual = apply_gate(x, 0, gate);
 
         assert_eq!(actual, expected);
     }
+
+    fn parse(input: &str) -> Result<Circuit> {
+        Day07::parse(input.as_bytes().lines())
+    }
+
+    /// The line, column and message of a parse error.
+    fn error_at(result: Result<Circuit>) -> (usize, usize, String) {
+        match result {
+            Err(PuzzleError::Parse { line, column, message, .. }) => (line, column, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!((2, 3, String::from("expected AND, OR, LSHIFT or RSHIFT, found 'XOR'")), error_at(parse("1 -> x\nx XOR y -> a")));
+        assert_eq!((1, 10, String::from("expected a shift of 0 to 15 bits, found '16'")), error_at(parse("1 LSHIFT 16 -> a")));
+        assert_eq!((1, 1, String::from("expected a signal or a wire name, found 'X'")), error_at(parse("X -> a")));
+        assert_eq!((1, 6, String::from("expected a wire name, found 'A'")), error_at(parse("1 -> A")));
+    }
+
+    #[test]
+    fn test_loop() {
+        let circuit = parse("b -> a\na -> b").unwrap();
+        assert!(matches!(Day07::part1(&circuit, &Params::default()), Err(PuzzleError::Unsolvable(_))));
+    }
 }
\ No newline at end of file
@@ -1,40 +1,62 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-
-    let mut part_1_total: usize = 0;
-    let mut part_2_total: usize = 0;
-
-    for line in input {
-        let s = &line.unwrap();
+use std::io::{ BufRead, Lines };
+
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day08;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 8,
+    title: "Matchsticks",
+    tags: &[Tag::Strings],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of strings", default_size: 300, generate },
+    solver: &Solver::<Day08>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_STRINGS, expected: "12", params: &[] },
+    Example { part: 2, input: EXAMPLE_STRINGS, expected: "19", params: &[] },
+];
+
+// The example strings exactly as they appear in an input file, quotes and escapes included
+const EXAMPLE_STRINGS: &str = r#"""
+"abc"
+"aaa\"aaa"
+"\x27""#;
+
+impl Solution for Day08 {
+    type Input = Vec<String>;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<String>> {
+        Ok(input.collect::<std::io::Result<Vec<String>>>()?)
+    }
 
-        // Part 1: The difference between the "code size" of a string and
+    fn part1(strings: &Vec<String>, _params: &Params) -> Result<Answer> {
+        // The difference between the "code size" of a string and
         // the "in-memory" size of a string is equivalent to the number of characters
         // that are removed when we convert the escape sequence to its real representation
-        let escaped_chars = count_escaped_chars(s);
-        part_1_total += escaped_chars;
-
+        let total: usize = strings.iter().map(|s| count_escaped_chars(s)).sum();
+        Ok(total.into())
+    }
 
-        // Part 2: This could be counted in the same loop as part 1 in order to save cycles,
-        // but I'm leaving it as separate for clarity.
+    fn part2(strings: &Vec<String>, _params: &Params) -> Result<Answer> {
         // Here, we just need to count how many characters in the string actually require an
         // escape backslash to be placed in front of them. The difference between the original
         // string "code size" and the new encoded size will simply be extra number of backslashes
         // required. We also need to +2 to this amount to account for the enclosing quotation marks.
-        let chars_to_escape = count_chars_to_escape(s);
-        part_2_total += chars_to_escape + 2;
-
+        let total: usize = strings.iter().map(|s| count_chars_to_escape(s) + 2).sum();
+        Ok(total.into())
     }
-
-    println!("Part 1: Total code size minus in-memory size is: {}", part_1_total);
-    println!("Part 2: Total re-encoded size minus original code size is: {}", part_2_total);
-
 }
 
 
-fn count_escaped_chars(s: &String) -> usize {
+fn count_escaped_chars(s: &str) -> usize {
     let mut count = 2; // leading and trailing quotes
 
     let mut prev = ' ';
@@ -59,7 +81,7 @@ fn count_escaped_chars(s: &String) -> usize {
     count
 }
 
-fn count_chars_to_escape(s: &String) -> usize {
+fn count_chars_to_escape(s: &str) -> usize {
     let mut count: usize = 0;
 
     // only backslashes and quotation marks require escapes
@@ -71,3 +93,21 @@ fn count_chars_to_escape(s: &String) -> usize {
 
     count
 }
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut line = String::from("\"");
+        for _ in 0..rng.range(0..=30) {
+            match rng.index(10) {
+                0 => line.push_str("\\\\"),
+                1 => line.push_str("\\\""),
+                2 => line.push_str(&format!("\\x{:02x}", rng.range(0..=255))),
+                _ => line.push_str(&rng.letters(1)),
+            }
+        }
+        line.push('"');
+        lines.push(line);
+    }
+    lines.join("\n")
+}
@@ -1,71 +1,138 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 use std::collections::HashMap;
-use itertools::{self, Itertools};
+use itertools::Itertools;
 
+use crate::cancel;
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ self, Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution, Visual };
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    // Traveling salesman. Solved with brute force.
+pub struct Day09;
 
-    let mut cities: HashMap<String, HashMap<String, u32>> = HashMap::new();
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 9,
+    title: "All in a Single Night",
+    tags: &[Tag::Graph, Tag::BruteForce],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of locations", default_size: 8, generate },
+    solver: &Solver::<Day09>::NEW,
+};
 
-    for line in input {
-        let s = &line.unwrap();
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_ROUTES, expected: "605", params: &[] },
+    Example { part: 2, input: EXAMPLE_ROUTES, expected: "982", params: &[] },
+];
 
-        // This replacement makes it easier to split the string
-        let s = s.replace(" to ", " = ");
-        let split: Vec<&str> = s.split(" = ").collect();
+const EXAMPLE_ROUTES: &str = "London to Dublin = 464\nLondon to Belfast = 518\nDublin to Belfast = 141";
 
-        let start_city = String::from(*split.get(0).unwrap());
-        let end_city = String::from(*split.get(1).unwrap());
-        let distance = String::from(*split.get(2).unwrap());
-        let distance = distance.parse::<u32>().unwrap();
 
-        // routes are bidirectional, according to the example in the problem statement
-        let routes = cities.entry(start_city.clone()).or_insert(HashMap::new());
-        routes.insert(end_city.clone(), distance);
-        
-        let routes = cities.entry(end_city).or_insert(HashMap::new());
-        routes.insert(start_city, distance);
+type Distances = HashMap<String, HashMap<String, u32>>;
 
-    }
-    
-    // Check that the graph is fully connected. If this is true, we can simplify routing
-    // because we don't need to check neighbors for each city.
-    let total_cities = cities.len();
-    for (_, routes) in &cities {
-        let outvertex_count = routes.len();
-        assert!(outvertex_count == total_cities-1);
-    }
+const ROUTE: Pattern = Pattern::new("{from} to {to} = {distance}");
 
-    // Each permutation is a potential route through all the cities. 
-    let keys = cities.keys().collect_vec();
-    let perms = keys.iter().permutations(keys.len());
+impl Solution for Day09 {
+    type Input = Distances;
+
+    const PARAMS: &'static [Param] = &[
+        Param::algorithm("How to search the routes: try every permutation, or held-karp dynamic programming", &["permutations", "held-karp"]),
+    ];
 
-    let mut shortest_route = u32::MAX;
-    let mut longest_route: u32 = 0;
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Distances> {
+        let mut cities: Distances = HashMap::new();
+
+        for (i, line) in input.enumerate() {
+            let line = line?;
+            let fields = ROUTE.parse(i+1, &line)?;
+
+            let start_city = String::from(fields.str("from"));
+            let end_city = String::from(fields.str("to"));
+            let distance: u32 = fields.number("distance")?;
+
+            // routes are bidirectional, according to the example in the problem statement
+            let routes = cities.entry(start_city.clone()).or_default();
+            routes.insert(end_city.clone(), distance);
+            
+            let routes = cities.entry(end_city).or_default();
+            routes.insert(start_city, distance);
 
-    for route in perms {
-        let current_route = length_of_route(route, &cities);
-        if current_route < shortest_route {
-            shortest_route = current_route;
         }
-        if current_route > longest_route {
-            longest_route = current_route;
+        
+        // Check that the graph is fully connected. If this is true, we can simplify routing
+        // because we don't need to check neighbors for each city.
+        let total_cities = cities.len();
+        for (city, routes) in &cities {
+            let outvertex_count = routes.len();
+            if outvertex_count != total_cities-1 {
+                return Err(PuzzleError::unsolvable(format!("{} is not connected to every other city", city)));
+            }
         }
+
+        Ok(cities)
     }
 
-    println!("Part 1: Distance of shortest route: {}", shortest_route);
-    println!("Part 2: Distance of the longest route: {}", longest_route);
+    fn part1(cities: &Distances, params: &Params) -> Result<Answer> {
+        // Traveling salesman. Solved with brute force by default.
+        let shortest = match params.algorithm() {
+            "held-karp" => held_karp(cities, u32::min),
+            _ => best_route(cities, u32::min)?.map(|(_, length)| length),
+        };
+        Ok(shortest.ok_or_else(|| PuzzleError::unsolvable("there are no cities to visit"))?.into())
+    }
+
+    fn part2(cities: &Distances, params: &Params) -> Result<Answer> {
+        let longest = match params.algorithm() {
+            "held-karp" => held_karp(cities, u32::max),
+            _ => best_route(cities, u32::max)?.map(|(_, length)| length),
+        };
+        Ok(longest.ok_or_else(|| PuzzleError::unsolvable("there are no cities to visit"))?.into())
+    }
 
+    fn visualize(cities: &Distances, _params: &Params) -> Result<Vec<Visual>> {
+        let mut visuals = Vec::new();
+        for (title, better) in [("Shortest route", u32::min as fn(u32, u32) -> u32), ("Longest route", u32::max)] {
+            let Some((route, length)) = best_route(cities, better)? else {
+                continue;
+            };
+            let mut lines: Vec<String> = route.windows(2)
+                .map(|leg| format!("{} to {} = {}", leg[0], leg[1], cities[leg[0]][leg[1]]))
+                .collect();
+            lines.push(format!("Total: {}", length));
+            visuals.push(Visual::new(format!("{}: {}", title, route.iter().join(" -> ")), lines.join("\n")));
+        }
+        Ok(visuals)
+    }
 }
 
-fn length_of_route(mut route: Vec<&&String>, distances: &HashMap<String, HashMap<String, u32>>) -> u32 {
+/// The best route through every city, by trying every permutation, along with its length.
+fn best_route(cities: &Distances, better: fn(u32, u32) -> u32) -> Result<Option<(Vec<&String>, u32)>> {
+    let keys = cities.keys().collect_vec();
+    let count = keys.len();
+    // With no cities the only permutation is empty, which is no route at all
+    if count == 0 {
+        return Ok(None);
+    }
+    let mut best: Option<(Vec<&String>, u32)> = None;
+    for route in keys.into_iter().permutations(count) {
+        cancel::check()?;
+        let length = length_of_route(route.clone(), cities);
+        if best.as_ref().is_none_or(|(_, best_length)| better(length, *best_length) != *best_length) {
+            best = Some((route, length));
+        }
+    }
+    Ok(best)
+}
+
+fn length_of_route(mut route: Vec<&String>, distances: &Distances) -> u32 {
     let mut total_distance: u32 = 0;
     let mut prev_node = route.pop().unwrap();
 
     for node in route.into_iter().rev() {
-        let distance_traveled = distances.get(*prev_node).unwrap().get(*node).unwrap();
+        let distance_traveled = distances.get(prev_node).unwrap().get(node).unwrap();
         
         total_distance += distance_traveled;
         
@@ -77,5 +144,64 @@ fn length_of_route(mut route: Vec<&&String>, distances: &HashMap<String, HashMap
         prev_node = node;
     }
     
-    return total_distance;
-}
\ No newline at end of file
+    total_distance
+}
+
+/// The length of the best route through every city, where `better` picks the better
+/// of two routes that visit the same cities and end in the same one. This takes
+/// O(2^n * n^2) time instead of the O(n!) of trying every permutation.
+fn held_karp(cities: &Distances, better: fn(u32, u32) -> u32) -> Option<u32> {
+    let names = cities.keys().collect_vec();
+    let count = names.len();
+    if count == 0 {
+        return None;
+    }
+
+    // best[visited][last] is the length of the best route through the cities in
+    // the `visited` bitmask that ends in `last`
+    let mut best: Vec<Vec<Option<u32>>> = vec![vec![None; count]; 1 << count];
+    for start in 0..count {
+        best[1 << start][start] = Some(0);
+    }
+    for visited in 1..1usize << count {
+        for last in 0..count {
+            let Some(length) = best[visited][last] else {
+                continue;
+            };
+            for next in (0..count).filter(|next| visited & (1 << next) == 0) {
+                let length = length + cities[names[last]][names[next]];
+                let entry = &mut best[visited | (1 << next)][next];
+                *entry = Some(entry.map_or(length, |other| better(other, length)));
+            }
+        }
+    }
+
+    best[(1 << count) - 1].iter().flatten().copied().reduce(better)
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let locations = generate::names(rng, size.max(2));
+    let mut lines = Vec::new();
+    for (i, from) in locations.iter().enumerate() {
+        for to in &locations[i+1..] {
+            lines.push(format!("{} to {} = {}", from, to, rng.range(10..=150)));
+        }
+    }
+    lines.join("\n")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_permutations_time_out() {
+        let input = PUZZLE.generator.generate(0, Some(12));
+        let cities = Day09::parse(input.as_bytes().lines()).unwrap();
+        let result = cancel::with_timeout(Some(Duration::from_millis(10)), || Day09::part1(&cities, &Params::new(Day09::PARAMS)));
+        assert!(matches!(result, Err(PuzzleError::TimedOut(_))));
+    }
+}
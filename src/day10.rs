@@ -1,5 +1,21 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
+
+use crate::error::{ PuzzleError, Result };
+use crate::generate::{ Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day10;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 10,
+    title: "Elves Look, Elves Say",
+    tags: &[Tag::Strings, Tag::Simulation],
+    examples: &[],
+    generator: Generator { size: "number of digits", default_size: 10, generate },
+    solver: &Solver::<Day10>::NEW,
+};
 
 #[derive(Debug)]
 struct NumberCount {
@@ -7,18 +23,33 @@ struct NumberCount {
     count: u32,
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    // count and say sequence
+impl Solution for Day10 {
+    type Input = String;
 
-    let input_str = input.last().unwrap().unwrap();
-  
-    let part_1 = length_after_n_iterations(input_str.clone(), 40);
-    let part_2 = length_after_n_iterations(input_str.clone(), 50);
+    const PARAMS: &'static [Param] = &[
+        Param::integer("part1-iterations", "How many times to apply look-and-say in part 1", 40),
+        Param::integer("part2-iterations", "How many times to apply look-and-say in part 2", 50),
+    ];
 
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<String> {
+        let (i, line) = input.enumerate().last()
+            .ok_or_else(|| PuzzleError::invalid_line(1, "", "expected a sequence of digits"))?;
+        let line = line?;
+        if let Some((pos, c)) = line.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            let token = &line[pos..pos+c.len_utf8()];
+            return Err(PuzzleError::invalid_token(i+1, &line, token, "expected only digits"));
+        }
+        Ok(line)
+    }
 
-    println!("Part 1: Length after 40 iterations: {}", part_1);
-    println!("Part 2: Length after 50 iterations: {}", part_2);
+    fn part1(input: &String, params: &Params) -> Result<Answer> {
+        // count and say sequence
+        Ok(length_after_n_iterations(input.clone(), params.integer("part1-iterations")).into())
+    }
 
+    fn part2(input: &String, params: &Params) -> Result<Answer> {
+        Ok(length_after_n_iterations(input.clone(), params.integer("part2-iterations")).into())
+    }
 }
 
 fn length_after_n_iterations(s: String, n: u32) -> usize {
@@ -28,7 +59,7 @@ fn length_after_n_iterations(s: String, n: u32) -> usize {
         result = iterate(result);
     }
 
-    return result.len();
+    result.len()
 }
 
 fn iterate(s: String) -> String {
@@ -67,5 +98,9 @@ fn iterate(s: String) -> String {
         result.push(num_count.value);
     }
 
-    return result;
+    result
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1)).map(|_| *rng.choose(&['1', '2', '3'])).collect()
 }
@@ -1,5 +1,29 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
+
+use crate::cancel;
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day11;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 11,
+    title: "Corporate Policy",
+    tags: &[Tag::Strings, Tag::Search],
+    examples: EXAMPLES,
+    generator: Generator { size: "unused, passwords are always 8 letters", default_size: 8, generate },
+    solver: &Solver::<Day11>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "abcdefgh", expected: "abcdffaa", params: &[] },
+    Example { part: 1, input: "ghijklmn", expected: "ghjaabcc", params: &[] },
+];
 
 // ascii code points
 const A: u8 = 97;
@@ -8,24 +32,38 @@ const L: u8 = 108;
 const O: u8 = 111;
 const Z: u8 = 122;
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    // all passwords have exactly 8 characters
-    let mut password: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 0];
+type Password = [u8; 8];
+
+impl Solution for Day11 {
+    type Input = Password;
+
+    fn parse<R: BufRead>(mut input: Lines<R>) -> Result<Password> {
+        // input should only have 1 line
+        let line = input.next()
+            .ok_or_else(|| PuzzleError::invalid_line(1, "", "expected a password"))??;
+
+        // all passwords have exactly 8 lowercase letters
+        if let Some((pos, c)) = line.char_indices().find(|(_, c)| !c.is_ascii_lowercase()) {
+            let token = &line[pos..pos+c.len_utf8()];
+            return Err(PuzzleError::invalid_token(1, &line, token, "passwords may only contain lowercase letters"));
+        }
+        if line.len() != 8 {
+            return Err(PuzzleError::invalid_line(1, &line, "passwords must be exactly 8 letters long"));
+        }
 
-    for line in input {
-        password = convert_from_string(line.unwrap());
-        break; // input should only have 1 line
+        Ok(convert_from_string(line))
     }
-    
-    let next_password = iterate(password);
-    println!("Part 1: Next password is {}", convert_to_string(next_password));
 
-    let next_password = iterate(next_password);
-    println!("Part 2: Next password is {}", convert_to_string(next_password));
+    fn part1(password: &Password, _params: &Params) -> Result<Answer> {
+        Ok(convert_to_string(iterate(*password)?).into())
+    }
 
+    fn part2(password: &Password, _params: &Params) -> Result<Answer> {
+        Ok(convert_to_string(iterate(iterate(*password)?)?).into())
+    }
 }
 
-fn iterate(mut current_password: [u8; 8]) -> [u8; 8] {
+fn iterate(mut current_password: [u8; 8]) -> Result<[u8; 8]> {
     // Brute force solution. Increment the current index until it reaches Z.
     // Once a character reaches Z, it needs to be cycle around to A, we increment
     // the next index, and then start incrementing again from the first index.
@@ -40,6 +78,7 @@ fn iterate(mut current_password: [u8; 8]) -> [u8; 8] {
     let mut current_index: usize = 0;
 
     while current_index < 8 {
+        cancel::check()?;
 
         let next_value = next_character_at_index(&current_password, current_index);
         current_password[current_index] = next_value;
@@ -55,11 +94,11 @@ fn iterate(mut current_password: [u8; 8]) -> [u8; 8] {
 
         // println!("Validating {}", convert_to_string(current_password));
         if validate(&current_password) {
-            return current_password;
+            return Ok(current_password);
         }
     }
 
-    panic!("Did not find any valid passwords!");
+    Err(PuzzleError::unsolvable("Did not find any valid passwords!"))
 }
 
 fn next_character_at_index(pw: &[u8; 8], index: usize) -> u8 {
@@ -76,7 +115,7 @@ fn next_character_at_index(pw: &[u8; 8], index: usize) -> u8 {
 }
 
 fn convert_to_string(password: [u8; 8]) -> String {
-    let mut password = password.clone();
+    let mut password = password;
     password.reverse();
     std::str::from_utf8(&password).expect("invalid utf-8 sequence").to_string()
 }
@@ -102,32 +141,29 @@ fn validate(password: &[u8; 8]) -> bool {
             contains_straight_run = password[i-2] == e+2 && password[i-1] == e+1
         }
 
-        if !contains_two_non_overlapping_pairs && i > 0 {
+        if !contains_two_non_overlapping_pairs && i > 0 && password[i-1] == *e {
+            // we have a candidate pair.
 
-            if password[i-1] == *e {
-                // we have a candidate pair.
+            if head_of_first_non_overlapping_pair > 7 {
+                // if we haven't found any pairs yet, use this as the first one
+                head_of_first_non_overlapping_pair = i-1;
 
-                if head_of_first_non_overlapping_pair > 7 {
-                    // if we haven't found any pairs yet, use this as the first one
-                    head_of_first_non_overlapping_pair = i-1;
+            } else if head_of_first_non_overlapping_pair < i - 2 {
 
-                } else if head_of_first_non_overlapping_pair < i - 2 {
-    
-                    // If the first non overlapping pair was found exactly two places ago,
-                    // that means that we're looking at a run of three identical letters
-                    //    a b c c c d e f
-                    //    0 1 2 3 4 5 6 7
-                    // 
-                    // e.g., if we are currently at i = 4, there's a candidate pair,
-                    // but it overlaps with the pair at 2-3
-
-                    contains_two_non_overlapping_pairs = true;
-                }
+                // If the first non overlapping pair was found exactly two places ago,
+                // that means that we're looking at a run of three identical letters
+                //    a b c c c d e f
+                //    0 1 2 3 4 5 6 7
+                // 
+                // e.g., if we are currently at i = 4, there's a candidate pair,
+                // but it overlaps with the pair at 2-3
+
+                contains_two_non_overlapping_pairs = true;
             }
         }
     }
 
-    return contains_straight_run && contains_two_non_overlapping_pairs;
+    contains_straight_run && contains_two_non_overlapping_pairs
 }
 
 fn convert_from_string(s: String) -> [u8; 8] {
@@ -140,13 +176,20 @@ fn convert_from_string(s: String) -> [u8; 8] {
     for char in current_password {
         password[j] = *char;
         
-        if j > 0 { // need the check here to avoid overflow after the last char
-            j -= 1;
-        }
+        // saturate here to avoid overflow after the last char
+        j = j.saturating_sub(1);
     }
     password
 }
 
+/// A random password. It never starts with `y` or `z`, so that there is always a
+/// next password and one after that.
+fn generate(rng: &mut Rng, _size: usize) -> String {
+    let first = (b'a' + rng.index(24) as u8) as char;
+    format!("{}{}", first, rng.letters(7))
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
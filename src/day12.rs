@@ -1,78 +1,158 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 use serde_json::Value; // https://github.com/serde-rs/json
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    // Parsing JSON
-
-    let input_str = input.last().unwrap().unwrap();
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day12;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 12,
+    title: "JSAbacusFramework.io",
+    tags: &[Tag::Json],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of values", default_size: 2000, generate },
+    solver: &Solver::<Day12>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "[1,2,3]", expected: "6", params: &[] },
+    Example { part: 1, input: r#"{"a":2,"b":4}"#, expected: "6", params: &[] },
+    Example { part: 1, input: "[[[3]]]", expected: "3", params: &[] },
+    Example { part: 1, input: r#"{"a":{"b":4},"c":-1}"#, expected: "3", params: &[] },
+    Example { part: 1, input: r#"{"a":[-1,1]}"#, expected: "0", params: &[] },
+    Example { part: 1, input: r#"[-1,{"a":1}]"#, expected: "0", params: &[] },
+    Example { part: 1, input: "[]", expected: "0", params: &[] },
+    Example { part: 1, input: "{}", expected: "0", params: &[] },
+    Example { part: 2, input: "[1,2,3]", expected: "6", params: &[] },
+    Example { part: 2, input: r#"[1,{"c":"red","b":2},3]"#, expected: "4", params: &[] },
+    Example { part: 2, input: r#"{"d":"red","e":[1,2,3,4],"f":5}"#, expected: "0", params: &[] },
+    Example { part: 2, input: r#"[1,"red",5]"#, expected: "6", params: &[] },
+];
+
+impl Solution for Day12 {
+    type Input = Value;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Value> {
+        // Parsing JSON
+        let mut input_str = String::new();
+        for line in input {
+            input_str += &line?;
+            input_str.push('\n');
+        }
 
-    let v: Value = serde_json::from_str(input_str.as_str()).unwrap();
-    let part_1 = sum(&v);
+        serde_json::from_str(input_str.as_str()).map_err(|e| {
+            let text = input_str.lines().nth(e.line().saturating_sub(1)).unwrap_or("");
+            PuzzleError::Parse {
+                line: e.line(),
+                column: e.column(),
+                text: text.to_string(),
+                message: e.to_string(),
+            }
+        })
+    }
 
-    let part_2 = sum_ignore_reds(&v);
+    fn part1(v: &Value, _params: &Params) -> Result<Answer> {
+        Ok(sum(v)?.into())
+    }
 
+    fn part2(v: &Value, _params: &Params) -> Result<Answer> {
+        Ok(sum_ignore_reds(v)?.into())
+    }
+}
 
-    println!("Part 1: Sum of all numbers in JSON: {}", part_1);
-    println!("Part 2: Sum of all numbers ignoring objects with 'red': {}", part_2);
+/// The value of a JSON number, which must be a whole number that fits in an `i64`.
+fn number(v: &Value) -> Result<i64> {
+    v.as_i64().ok_or_else(|| PuzzleError::unsolvable(format!("{} is not a whole number that fits in 64 bits", v)))
+}
 
+/// Adds up `values`, failing if the total does not fit in an `i64`.
+fn total(values: impl Iterator<Item = Result<i64>>) -> Result<i64> {
+    let mut s: i64 = 0;
+    for value in values {
+        s = s.checked_add(value?).ok_or_else(|| PuzzleError::unsolvable("the sum is too large"))?;
+    }
+    Ok(s)
 }
 
-fn sum(v: &Value) -> i64 {
+fn sum(v: &Value) -> Result<i64> {
     if v.is_number() {
-        return v.as_i64().unwrap();
+        return number(v);
     }
     if v.is_array() {
         let array = v.as_array().unwrap();
-        let mut s = 0;
-        for vi in array {
-            s += sum(vi);
-        }
-        return s;
+        return total(array.iter().map(sum));
     }
     if v.is_object() {
         let obj = v.as_object().unwrap();
-        let mut s = 0;
-        for vi in obj.values() {
-            s += sum(vi);
-        }
-        return s;
+        return total(obj.values().map(sum));
     }
 
-    return 0;
+    Ok(0)
 }
 
 
-fn sum_ignore_reds(v: &Value) -> i64 {
+fn sum_ignore_reds(v: &Value) -> Result<i64> {
     if v.is_number() {
-        return v.as_i64().unwrap();
+        return number(v);
     }
     if v.is_array() {
         let array = v.as_array().unwrap();
-        let mut s = 0;
-        for vi in array {
-            s += sum_ignore_reds(vi);
-        }
-        return s;
+        return total(array.iter().map(sum_ignore_reds));
     }
     if v.is_object() {
         let obj = v.as_object().unwrap();
-        let mut s = 0;
-        for vi in obj.values() {
-            if vi.is_string() {
-                let str_val = vi.as_str().unwrap();
-                if str_val == "red" {
-                    return 0;
-                }
-            }
-            s += sum_ignore_reds(vi);
+        if obj.values().any(|vi| vi.as_str() == Some("red")) {
+            return Ok(0);
         }
-        return s;
+        return total(obj.values().map(sum_ignore_reds));
     }
 
-    return 0;
+    Ok(0)
 }
 
+/// A JSON array of about `size` values, nested in arrays and objects, some of them red.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut budget = size;
+    generate_value(rng, &mut budget, 0)
+}
+
+fn generate_value(rng: &mut Rng, budget: &mut usize, depth: u32) -> String {
+    *budget = budget.saturating_sub(1);
+    let nested = depth == 0 || (*budget > 0 && depth < 6 && rng.chance(1, 4));
+    if !nested {
+        return match rng.index(5) {
+            0 => String::from("\"red\""),
+            1 => format!("\"{}\"", rng.choose(&["blue", "green", "orange", "violet", "yellow"])),
+            _ => rng.range(-50..=200).to_string(),
+        };
+    }
+
+    // The top level holds whatever is left of the budget, and object keys stay distinct
+    let (object, len) = match depth {
+        0 => (false, usize::MAX),
+        _ => (rng.chance(1, 2), rng.range(1..=8) as usize),
+    };
+    let mut items = Vec::new();
+    while items.len() < len && (*budget > 0 || items.is_empty()) {
+        let value = generate_value(rng, budget, depth + 1);
+        items.push(match object {
+            true => format!("\"{}\":{}", (b'a' + items.len() as u8) as char, value),
+            false => value,
+        });
+    }
+    match object {
+        true => format!("{{{}}}", items.join(",")),
+        false => format!("[{}]", items.join(",")),
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,10 +183,18 @@ mod tests {
 
     fn validate_sum(input: &str, expected: i64) {
         let v: Value = serde_json::from_str(input).unwrap();
-        let actual = sum(&v);
+        let actual = sum(&v).unwrap();
         assert!(expected == actual);
     }
 
+    #[test]
+    fn test_sum_invalid_numbers() {
+        for input in ["[1.5]", "[99999999999999999999]", "[9223372036854775807,1]"] {
+            let v: Value = serde_json::from_str(input).unwrap();
+            assert!(matches!(sum(&v), Err(PuzzleError::Unsolvable(_))), "input={}", input);
+        }
+    }
+
     #[test]
     fn test_sum_ignore_reds() {
         validate_sum_ignore_reds(r#"{"a":2,"b":4}"#, 6);
@@ -118,7 +206,7 @@ mod tests {
 
     fn validate_sum_ignore_reds(input: &str, expected: i64) {
         let v: Value = serde_json::from_str(input).unwrap();
-        let actual = sum_ignore_reds(&v);
+        let actual = sum_ignore_reds(&v).unwrap();
         assert!(expected == actual, "input={}, expected={}, actual={}", input, expected, actual);
     }
 }
@@ -1,7 +1,49 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 use std::collections::HashMap;
-use itertools::{self, Itertools};
+use itertools::Itertools;
+
+use crate::cancel;
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ self, Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day13;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 13,
+    title: "Knights of the Dinner Table",
+    tags: &[Tag::Graph, Tag::BruteForce],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of guests", default_size: 8, generate },
+    solver: &Solver::<Day13>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: concat!(
+            "Alice would gain 54 happiness units by sitting next to Bob.\n",
+            "Alice would lose 79 happiness units by sitting next to Carol.\n",
+            "Alice would lose 2 happiness units by sitting next to David.\n",
+            "Bob would gain 83 happiness units by sitting next to Alice.\n",
+            "Bob would lose 7 happiness units by sitting next to Carol.\n",
+            "Bob would lose 63 happiness units by sitting next to David.\n",
+            "Carol would lose 62 happiness units by sitting next to Alice.\n",
+            "Carol would gain 60 happiness units by sitting next to Bob.\n",
+            "Carol would gain 55 happiness units by sitting next to David.\n",
+            "David would gain 46 happiness units by sitting next to Alice.\n",
+            "David would lose 7 happiness units by sitting next to Bob.\n",
+            "David would gain 41 happiness units by sitting next to Carol.",
+        ),
+        expected: "330",
+        params: &[],
+    },
+];
 
 
 #[derive(Debug)]
@@ -11,31 +53,48 @@ struct Entry {
     neighbor: String,
 }
 
-type HappinessMap = HashMap<String, HashMap<String, i32>>;
+pub type HappinessMap = HashMap<String, HashMap<String, i32>>;
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+impl Solution for Day13 {
+    type Input = HappinessMap;
 
-    let mut happiness_map: HappinessMap = HashMap::new();
+    const PARAMS: &'static [Param] = &[
+        Param::algorithm("How to search the seatings: try every permutation, or held-karp dynamic programming", &["permutations", "held-karp"]),
+    ];
 
-    for line in input {
-        let entry = line_to_entry(line.unwrap());
-        
-        if !happiness_map.contains_key(&entry.principal) {
-            let neighbors: HashMap<String, i32> = HashMap::new();
-            happiness_map.insert(entry.principal.clone(), neighbors);
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<HappinessMap> {
+        let mut happiness_map: HappinessMap = HashMap::new();
+
+        for (i, line) in input.enumerate() {
+            let entry = line_to_entry(i+1, &line?)?;
+            let neighbors_for_principal = happiness_map.entry(entry.principal).or_default();
+            neighbors_for_principal.insert(entry.neighbor, entry.change_in_happiness);
+        }
+
+        if happiness_map.is_empty() {
+            return Err(PuzzleError::unsolvable("there are no guests to seat"));
+        }
+
+        // Every guest must have a happiness value for sitting next to every other guest
+        let guest_count = happiness_map.len();
+        for (guest, neighbors) in &happiness_map {
+            if neighbors.len() != guest_count-1 {
+                return Err(PuzzleError::unsolvable(format!("{} does not have a happiness value for every other guest", guest)));
+            }
         }
 
-        let neighbors_for_principal = happiness_map.get_mut(&entry.principal).unwrap();
-        neighbors_for_principal.insert(entry.neighbor, entry.change_in_happiness);
+        Ok(happiness_map)
     }
 
-    let part1 = part1_brute_force(&happiness_map);
+    fn part1(happiness_map: &HappinessMap, params: &Params) -> Result<Answer> {
+        Ok(best_seating(happiness_map, params)?.into())
+    }
 
-    add_self_to_map(&mut happiness_map);
-    let part2 = part1_brute_force(&happiness_map);
-    
-    println!("Part 1: Total change in happiness: {}", part1);
-    println!("Part 2: Total change in happiness: {}", part2);
+    fn part2(happiness_map: &HappinessMap, params: &Params) -> Result<Answer> {
+        let mut happiness_map = happiness_map.clone();
+        add_self_to_map(&mut happiness_map);
+        Ok(best_seating(&happiness_map, params)?.into())
+    }
 }
 
 fn add_self_to_map(happiness_map: &mut HappinessMap) {
@@ -49,20 +108,28 @@ fn add_self_to_map(happiness_map: &mut HappinessMap) {
     happiness_map.insert(String::from("self"), neighbors_of_self);
 }
 
-fn part1_brute_force(happiness: &HappinessMap) -> i32 {
+fn best_seating(happiness_map: &HappinessMap, params: &Params) -> Result<i32> {
+    match params.algorithm() {
+        "held-karp" => Ok(held_karp(happiness_map)),
+        _ => part1_brute_force(happiness_map),
+    }
+}
+
+fn part1_brute_force(happiness: &HappinessMap) -> Result<i32> {
     let keys = happiness.keys().collect_vec();
     let perms = keys.iter().permutations(keys.len());
 
     let mut highest_happiness = i32::MIN;
 
     for permutation in perms {
-        let h = measure_happiness_for_permutation(permutation, &happiness);
+        cancel::check()?;
+        let h = measure_happiness_for_permutation(permutation, happiness);
         if h > highest_happiness {
             highest_happiness = h;
         }
     }
 
-    return highest_happiness;
+    Ok(highest_happiness)
 }
 
 fn measure_happiness_for_permutation(permutation: Vec<&&String>, happiness_map: &HappinessMap) -> i32 {
@@ -96,25 +163,75 @@ fn measure_happiness_for_individual(principal: String, neighbor1: String, neighb
     let map = &happiness_map[&principal];
     let a = map[&neighbor1];
     let b = map[&neighbor2];
-    return a+b;
+    a+b
+}
+
+/// The happiness of the best seating, found in O(2^n * n^2) time instead of the
+/// O(n!) of trying every permutation. The table is round, so the first guest can
+/// always sit in the first seat, and the others are added one at a time.
+fn held_karp(happiness_map: &HappinessMap) -> i32 {
+    let guests = happiness_map.keys().collect_vec();
+    let count = guests.len();
+    let pair = |a: usize, b: usize| happiness_map[guests[a]][guests[b]] + happiness_map[guests[b]][guests[a]];
+
+    // best[seated][last] is the happiness of the best row of the guests in the
+    // `seated` bitmask that starts with the first guest and ends with `last`
+    let mut best: Vec<Vec<Option<i32>>> = vec![vec![None; count]; 1 << count];
+    best[1][0] = Some(0);
+    for seated in 1..1usize << count {
+        for last in 0..count {
+            let Some(happiness) = best[seated][last] else {
+                continue;
+            };
+            for next in (0..count).filter(|next| seated & (1 << next) == 0) {
+                let happiness = happiness + pair(last, next);
+                let entry = &mut best[seated | (1 << next)][next];
+                *entry = Some(entry.map_or(happiness, |other| other.max(happiness)));
+            }
+        }
+    }
+
+    // Close the circle by seating the last guest next to the first
+    (1..count)
+        .filter_map(|last| best[(1 << count) - 1][last].map(|happiness| happiness + pair(last, 0)))
+        .max()
+        .unwrap_or(0)
+}
+
+const ENTRY: Pattern = Pattern::new("{guest} would {gain or lose} {units} happiness units by sitting next to {neighbor}.");
+
+fn line_to_entry(line_number: usize, line: &str) -> Result<Entry> {
+    let fields = ENTRY.parse(line_number, line)?;
+    let sign = fields.choice("gain or lose", &[("gain", 1), ("lose", -1)])?;
+    let units: i32 = fields.number("units")?;
+
+    Ok(Entry {
+        principal: String::from(fields.str("guest")),
+        change_in_happiness: sign * units,
+        neighbor: String::from(fields.str("neighbor")),
+    })
 }
 
-fn line_to_entry(line: String) -> Entry {
-    // Example input: Alice would gain 54 happiness units by sitting next to Bob.
-    let line = line.replace(".", "");
-    let split: Vec<&str>= line.split(" ").collect();
-    assert!(split.len() == 11);
-
-    let principal = String::from(split[0]);
-    let neighbor = String::from(split[10]);
-    let mut value = split[3].parse::<i32>().unwrap();
-    if split[2] == "lose" {
-        value *= -1;
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let guests = generate::names(rng, size.max(2));
+    let mut lines = Vec::new();
+    for guest in &guests {
+        for neighbor in guests.iter().filter(|&neighbor| neighbor != guest) {
+            let change = rng.range(-99..=99);
+            let (verb, units) = if change < 0 { ("lose", -change) } else { ("gain", change) };
+            lines.push(format!("{} would {} {} happiness units by sitting next to {}.", guest, verb, units, neighbor));
+        }
     }
-    
-    return Entry {
-        principal: principal,
-        change_in_happiness: value,
-        neighbor: neighbor,
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_guest_list_is_rejected() {
+        let result = Day13::parse("".as_bytes().lines());
+        assert!(matches!(result, Err(PuzzleError::Unsolvable(_))));
     }
 }
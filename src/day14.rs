@@ -1,13 +1,40 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-use itertools::Itertools;
-
-const TIME_LIMIT: u32 = 2503;
+use std::io::{ BufRead, Lines };
+
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ self, Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day14;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 14,
+    title: "Reindeer Olympics",
+    tags: &[Tag::Simulation],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of reindeer", default_size: 9, generate },
+    solver: &Solver::<Day14>::NEW,
+};
+
+/// Examples from the problem statement, where the race lasts 1000 seconds.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_REINDEER, expected: "1120", params: EXAMPLE_RACE },
+    Example { part: 2, input: EXAMPLE_REINDEER, expected: "689", params: EXAMPLE_RACE },
+];
+
+const EXAMPLE_REINDEER: &str = concat!(
+    "Comet can fly 14 km/s for 10 seconds, but then must rest for 127 seconds.\n",
+    "Dancer can fly 16 km/s for 11 seconds, but then must rest for 162 seconds.\n",
+);
+
+const EXAMPLE_RACE: &[(&str, &str)] = &[("time-limit", "1000")];
 
 #[derive(Debug)]
-struct Reindeer {
+pub struct Reindeer {
     name: String,
     speed: u32,
     fly_time: u32,
@@ -35,26 +62,37 @@ impl Reindeer {
     }
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+impl Solution for Day14 {
+    type Input = Vec<Reindeer>;
 
-    let mut reindeer: Vec<Reindeer> = Vec::new();
+    const PARAMS: &'static [Param] = &[
+        Param::integer("time-limit", "How many seconds the race lasts", 2503),
+    ];
 
-    for line in input {
-        let r = parse_line(line.unwrap());
-        reindeer.push(r);
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<Reindeer>> {
+        let mut reindeer = Vec::new();
+        for (i, line) in input.enumerate() {
+            reindeer.push(parse_line(i+1, &line?)?);
+        }
+        Ok(reindeer)
     }
 
-    let part_1 = part1(&reindeer);
-    let part2 = part2(&reindeer, TIME_LIMIT);
-    
-    println!("Part 1: Distance traveled by winning reindeer: {}", part_1);
-    println!("Part 2: Total points for winning reindeer: {}", part2);
+    fn part1(reindeer: &Vec<Reindeer>, params: &Params) -> Result<Answer> {
+        Ok(part1(reindeer, params.integer("time-limit")).into())
+    }
+
+    fn part2(reindeer: &Vec<Reindeer>, params: &Params) -> Result<Answer> {
+        if reindeer.is_empty() {
+            return Err(PuzzleError::unsolvable("there are no reindeer in the race"));
+        }
+        Ok(part2(reindeer, params.integer("time-limit")).into())
+    }
 }
 
-fn part1(reindeer: &Vec<Reindeer>) -> u32 {
+fn part1(reindeer: &[Reindeer], time_limit: u32) -> u32 {
     let mut farthest_distance = 0;
     for r in reindeer {
-        let d = r.distance_after_n_seconds(TIME_LIMIT);
+        let d = r.distance_after_n_seconds(time_limit);
         if d > farthest_distance {
             farthest_distance = d;
         }
@@ -62,7 +100,7 @@ fn part1(reindeer: &Vec<Reindeer>) -> u32 {
     farthest_distance
 }
 
-fn part2(reindeer: &Vec<Reindeer>, time_limit: u32) -> u32 {
+fn part2(reindeer: &[Reindeer], time_limit: u32) -> u32 {
     let mut points: HashMap<String, u32> = reindeer.iter().map(|r| (r.name.clone(), 0)).collect();
     
     for i in 1..(time_limit+1) {
@@ -81,19 +119,32 @@ fn part2(reindeer: &Vec<Reindeer>, time_limit: u32) -> u32 {
 
     }
 
-    return *points.values().max().unwrap();
+    *points.values().max().unwrap()
 }
 
-fn parse_line(line: String) -> Reindeer {
-    let s = line.split(" ").collect_vec();
-    let name = String::from(s[0]);
-    let speed = s[3].parse::<u32>().unwrap();
-    let fly_time = s[6].parse::<u32>().unwrap();
-    let rest_time = s[13].parse::<u32>().unwrap();
+const REINDEER: Pattern = Pattern::new("{name} can fly {speed} km/s for {fly time} seconds, but then must rest for {rest time} seconds.");
+
+fn parse_line(line_number: usize, line: &str) -> Result<Reindeer> {
+    let fields = REINDEER.parse(line_number, line)?;
+    let name = String::from(fields.str("name"));
+    let speed = fields.number("speed")?;
+    let fly_time: u32 = fields.number("fly time")?;
+    let rest_time: u32 = fields.number("rest time")?;
+    if fly_time + rest_time == 0 {
+        return Err(PuzzleError::invalid_line(line_number, line, "fly and rest times cannot both be zero"));
+    }
 
-    return Reindeer {name, speed, fly_time, rest_time}
+    Ok(Reindeer {name, speed, fly_time, rest_time})
 }
 
+fn generate(rng: &mut Rng, size: usize) -> String {
+    generate::names(rng, size.max(1))
+        .iter()
+        .map(|name| format!("{} can fly {} km/s for {} seconds, but then must rest for {} seconds.",
+            name, rng.range(2..=30), rng.range(2..=20), rng.range(20..=180)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
 
 #[cfg(test)]
@@ -183,25 +234,4 @@ mod tests {
         assert!(1056 == distance);
     }
 
-    #[test]
-    fn part2_winning_reindeer() {
-        let comet = Reindeer {
-            name: String::from("comet"),
-            speed: 14,
-            fly_time: 10,
-            rest_time: 127,
-        };
-
-        let dancer = Reindeer {
-            name: String::from("dancer"),
-            speed: 16,
-            fly_time: 11,
-            rest_time: 162,
-        };
-
-        let time_limit = 1000;
-        let reindeer: Vec<Reindeer> = vec![comet, dancer];
-        let result = part2(&reindeer, time_limit);
-        assert!(689 == result, "{} != 689", result);
-    }
 }
@@ -1,13 +1,39 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-use itertools::Itertools;
-
-type Recipe = (u32, u32, u32, u32);
+use std::io::{ BufRead, Lines };
+
+use crate::cancel;
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day15;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 15,
+    title: "Science for Hungry People",
+    tags: &[Tag::Optimization, Tag::BruteForce],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of ingredients, up to 4", default_size: 4, generate },
+    solver: &Solver::<Day15>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_INGREDIENTS, expected: "62842880", params: &[] },
+    Example { part: 2, input: EXAMPLE_INGREDIENTS, expected: "57600000", params: &[] },
+];
+
+const EXAMPLE_INGREDIENTS: &str = concat!(
+    "Butterscotch: capacity -1, durability -2, flavor 6, texture 3, calories 8\n",
+    "Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3",
+);
 
 #[derive(Debug)]
 #[derive(PartialEq)]
-struct Ingredient {
+pub struct Ingredient {
     name: String,
     capacity: i32,
     durability: i32,
@@ -17,161 +43,216 @@ struct Ingredient {
 }
 
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    /* 
-    Simple solution that iterates over every possible combination
-    of ingredients (a "recipe") that adds up to 100 teaspoons, and returns 
-    the top score. Constraints from the problem statement are hardcoded into the
-    code here, so this solution will not work for different inputs.
-
-    The total number of possible recipes is 156k
-    (https://math.stackexchange.com/questions/2592528/compositions-of-4-positive-integers-summing-up-to-100).
+impl Solution for Day15 {
+    type Input = Vec<Ingredient>;
 
-    We can avoid performing calculations on recipes that will result in a score of 0
-    by observing that recipes have constraints. If these constraints are not met,
-    the recipe will be guaranteed to have a score of 0.
+    const PARAMS: &'static [Param] = &[
+        Param::algorithm(
+            "How to find the best recipe: try every recipe, or leave out the recipes that must score 0",
+            &["full-enumeration", "constrained"]),
+    ];
 
-    Consider the following input matrix, multiplying a recipe vector.
-
-                        INPUT                  RECIPE
-                 spr  pb  fros  sug
-    capacity   [  5   -1    0   -1  ]   [ amt_sprinkles    ]
-    durability [ -1    3   -1    0  ]   [ amt_peanutbutter ]
-    flavor     [  0    0    4    0  ]   [ amt_frosting     ]
-    texture    [  0    0    0    2  ]   [ amt_sugar        ]
-
-    The resulting matrix contains the scores for each property for that 
-    particular recipe. If any of the scores are negative, they must be 
-    converted into 0, and thus the total score will also be 0.
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<Ingredient>> {
+        let mut ingredients = Vec::new();
+        for (i, line) in input.enumerate() {
+            ingredients.push(parse_line(i+1, &line?)?);
+        }
+        Ok(ingredients)
+    }
 
-    So, recipes must be constrained in such a way that the proportions
-    of ingredients would not result in a negative score for any property.
-    In the first row (capacity), as long as the total amount of peanut butter
-    and sugar is less than 5x the amount of sprinkles, the calories score
-    will always be positive. Also, the amount of sprinkles must be nonzero,
-    since it is the only ingredient that contributes positively to the
-    capacity score.
+    fn part1(ingredients: &Vec<Ingredient>, params: &Params) -> Result<Answer> {
+        Ok(best_scores_with(ingredients, params)?.0.into())
+    }
 
-    In the second row, the amount of sprinkles and frosting must be less than
-    3x the amount of peanut butter in order for the durability score
-    to always be positive. Also, the amount of peanut butter must be nonzero
-    since it is the only ingredient that contributes positively to the capacity score.
+    fn part2(ingredients: &Vec<Ingredient>, params: &Params) -> Result<Answer> {
+        Ok(best_scores_with(ingredients, params)?.1.into())
+    }
+}
 
-    Flavor will always be positive as long as the amount of frosting is non-zero.
+fn best_scores_with(ingredients: &[Ingredient], params: &Params) -> Result<(u64, u64)> {
+    match params.algorithm() {
+        "constrained" => best_scores_constrained(ingredients),
+        _ => best_scores_full_enumeration(ingredients),
+    }
+}
 
-    Texture will always be positive as long as the amount of sugar is non-zero.
-    */
+/// Tries every way of dividing the 100 teaspoons between the ingredients, however
+/// many there are.
+fn best_scores_full_enumeration(ingredients: &[Ingredient]) -> Result<(u64, u64)> {
+    let mut best = (0, 0);
+    if !ingredients.is_empty() {
+        let mut amounts = vec![0; ingredients.len()];
+        enumerate_recipes(ingredients, &mut amounts, 0, 100, &mut best)?;
+    }
+    Ok(best)
+}
 
-    let mut ingredients: Vec<Ingredient> = Vec::new();
+fn enumerate_recipes(ingredients: &[Ingredient], amounts: &mut [i64], i: usize, remaining: i64, best: &mut (u64, u64)) -> Result<()> {
+    cancel::check()?;
+    if i == amounts.len() - 1 {
+        amounts[i] = remaining;
+        let property = |value: fn(&Ingredient) -> i32| -> i64 {
+            ingredients.iter().zip(amounts.iter()).map(|(ingredient, amount)| i64::from(value(ingredient)) * amount).sum()
+        };
+        let score = [
+            property(|ingredient| ingredient.capacity),
+            property(|ingredient| ingredient.durability),
+            property(|ingredient| ingredient.flavor),
+            property(|ingredient| ingredient.texture),
+        ].iter().map(|&total| total.max(0) as u64).product();
+
+        best.0 = best.0.max(score);
+        if property(|ingredient| ingredient.calories) == 500 {
+            best.1 = best.1.max(score);
+        }
+        return Ok(());
+    }
 
-    for line in input {
-        let ingredient = parse_line(line.unwrap());
-        ingredients.push(ingredient);
+    for amount in 0..=remaining {
+        amounts[i] = amount;
+        enumerate_recipes(ingredients, amounts, i + 1, remaining - amount, best)?;
     }
+    Ok(())
+}
 
-    let valid_recipes = generate_valid_combinations_for_part_1();
-    println!("Count of valid recipes: {}", valid_recipes.len());
+/// Capacity, durability, flavor, texture and calories.
+type Properties = [i64; 5];
 
-    let mut part_1 = 0;
-    let mut part_2 = 0;
+fn properties(ingredient: &Ingredient) -> Properties {
+    [ingredient.capacity, ingredient.durability, ingredient.flavor, ingredient.texture, ingredient.calories].map(i64::from)
+}
 
-    for recipe in valid_recipes {
-        let (p1, p2) = recipe_score(recipe, &ingredients);
-        if p1 > part_1 {
-            part_1 = p1;
-        }
-        if p2 > part_2 {
-            part_2 = p2;
-        }
+/// Like [`best_scores_full_enumeration`], but gives up on a recipe as soon as one
+/// of the scored properties can no longer end up positive, since its score will
+/// be 0 however the rest of the teaspoons are spent.
+///
+/// With the real ingredients only one of them adds to each property, so most
+/// recipes are left out early.
+fn best_scores_constrained(ingredients: &[Ingredient]) -> Result<(u64, u64)> {
+    let properties: Vec<Properties> = ingredients.iter().map(properties).collect();
+    // The most one teaspoon of any of the ingredients from each one on adds to each property
+    let mut best_after: Vec<Properties> = vec![[i64::MIN; 5]; properties.len() + 1];
+    for i in (0..properties.len()).rev() {
+        best_after[i] = std::array::from_fn(|k| properties[i][k].max(best_after[i + 1][k]));
     }
 
-    println!("Part 1: Total score of highest-scoring cookie: {}", part_1);
-    println!("Part 2: Total score of highest-scoring cookie: {}", part_2);
-}
-
-fn recipe_score(recipe: Recipe, ingredients: &Vec<Ingredient>) -> (u32, u32) {
-    let mut capacity = 0;
-    let mut durability = 0;
-    let mut flavor = 0;
-    let mut texture = 0;
-    let mut calories = 0;
-
-
-    for ingredient in ingredients {
-        let mut amount = recipe.0;
-        if ingredient.name == String::from("PeanutButter") {
-            amount = recipe.1;
-        } else if ingredient.name == String::from("Frosting") {
-            amount = recipe.2;
-        } else if ingredient.name == String::from("Sugar") {
-            amount = recipe.3;
-        }
-        let amount = i32::try_from(amount).unwrap();
-        capacity += amount * ingredient.capacity;
-        durability += amount * ingredient.durability;
-        flavor += amount * ingredient.flavor;
-        texture += amount * ingredient.texture;
-        calories += amount * ingredient.calories;
-    }
-
-    let total = capacity * durability * flavor * texture;
-    if total < 0 {
-        return (0, 0);
-    } else if calories != 500 {
-        return (u32::try_from(total).unwrap(), 0);
-    } else {
-        let t = u32::try_from(total).unwrap();
-        return (t, t);
+    let mut best = (0, 0);
+    if !ingredients.is_empty() {
+        constrained_recipes(&properties, &best_after, 0, 100, [0; 5], &mut best)?;
     }
+    Ok(best)
 }
 
+fn constrained_recipes(
+    properties: &[Properties],
+    best_after: &[Properties],
+    i: usize,
+    remaining: i64,
+    totals: Properties,
+    best: &mut (u64, u64),
+) -> Result<()> {
+    cancel::check()?;
+    if (0..4).any(|k| totals[k] + remaining * best_after[i][k] <= 0) {
+        return Ok(());
+    }
 
-fn generate_valid_combinations_for_part_1() -> Vec<Recipe> {
-    let mut valid_recipes: Vec<Recipe> = Vec::new();
-
-    for sprinkles in 1..101 {
-        for peanut_butter in 1..101 {
-            for frosting in 1..101 {
-                for sugar in 1..101 {
-
-                    // constraints
-                    if sprinkles + peanut_butter + frosting + sugar != 100 {
-                        continue
-                    }
-                    if peanut_butter + sugar >= 5 * sprinkles {
-                        continue
-                    }
-                    if sprinkles + frosting >= 3 * peanut_butter {
-                        continue
-                    }
-                    // all ingredient amounts must be nonzero
-
-                    valid_recipes.push((sprinkles, peanut_butter, frosting, sugar));
-                }
-            }
+    let add = |amount: i64| -> Properties { std::array::from_fn(|k| totals[k] + amount * properties[i][k]) };
+    if i == properties.len() - 1 {
+        let totals = add(remaining);
+        let score = totals[..4].iter().map(|&total| total.max(0) as u64).product();
+        best.0 = best.0.max(score);
+        if totals[4] == 500 {
+            best.1 = best.1.max(score);
         }
+        return Ok(());
     }
 
-    return valid_recipes;
+    for amount in 0..=remaining {
+        constrained_recipes(properties, best_after, i + 1, remaining - amount, add(amount), best)?;
+    }
+    Ok(())
 }
 
 
-fn parse_line(line: String) -> Ingredient {
-    let line = line.replace(",", "");
-    let line = line.replace(":", "");
-    let s = line.split(" ").collect_vec();
+const INGREDIENT: Pattern = Pattern::new("{name}: capacity {capacity}, durability {durability}, flavor {flavor}, texture {texture}, calories {calories}");
 
-    let name = String::from(s[0]);
-    let capacity = s[2].parse::<i32>().unwrap();
-    let durability = s[4].parse::<i32>().unwrap();
-    let flavor = s[6].parse::<i32>().unwrap();
-    let texture = s[8].parse::<i32>().unwrap();
-    let calories = s[10].parse::<i32>().unwrap();
-
-    return Ingredient { name, capacity, durability, flavor, texture, calories};
+fn parse_line(line_number: usize, line: &str) -> Result<Ingredient> {
+    let fields = INGREDIENT.parse(line_number, line)?;
+    Ok(Ingredient {
+        name: String::from(fields.str("name")),
+        capacity: fields.number("capacity")?,
+        durability: fields.number("durability")?,
+        flavor: fields.number("flavor")?,
+        texture: fields.number("texture")?,
+        calories: fields.number("calories")?,
+    })
 }
 
+const INGREDIENTS: &[&str] = &[
+    "Butterscotch", "Candy", "Chocolate", "Cinnamon", "Coconut", "Frosting",
+    "Honey", "PeanutButter", "Sprinkles", "Sugar", "Toffee", "Vanilla",
+];
+
+/// Full enumeration tries over 96 million recipes for six ingredients, so inputs
+/// have no more than the real four.
+const MAX_INGREDIENTS: usize = 4;
+
+/// `size` ingredients, up to four, with random names, properties between -3 and 5
+/// and calories between 1 and 9.
+///
+/// Random properties mostly leave some property negative in every recipe, so the
+/// ingredients are built around a hidden recipe of 100 teaspoons and 500 calories.
+/// The calories of the last two ingredients differ by one, so their amounts can
+/// always make up the calories left by the others. Then properties are raised until
+/// the hidden recipe scores above zero, so that both parts have a positive answer.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut names = INGREDIENTS.to_vec();
+    rng.shuffle(&mut names);
+    names.truncate(size.clamp(1, MAX_INGREDIENTS));
+    let count = names.len();
+
+    // The amount and calories of each ingredient in the hidden recipe
+    let mut amounts: Vec<i64> = Vec::new();
+    let mut calories: Vec<i64> = Vec::new();
+    if count == 1 {
+        amounts.push(100);
+        calories.push(5);
+    } else {
+        // At most 40 teaspoons between the others leaves the last two an average
+        // between 1 and 9 calories to make up
+        for _ in 0..count - 2 {
+            amounts.push(rng.range(1..=20));
+            calories.push(rng.range(1..=9));
+        }
+        let teaspoons = 100 - amounts.iter().sum::<i64>();
+        let remaining = 500 - amounts.iter().zip(&calories).map(|(a, c)| a * c).sum::<i64>();
+        let low = (remaining / teaspoons).min(8);
+        // a * low + b * (low + 1) = remaining, with a + b = teaspoons
+        let high_amount = remaining - low * teaspoons;
+        amounts.extend([teaspoons - high_amount, high_amount]);
+        calories.extend([low, low + 1]);
+    }
+
+    // Each property of every ingredient, one property at a time
+    let mut properties: [Vec<i64>; 4] = [(); 4].map(|_| (0..count).map(|_| rng.range(-3..=5)).collect());
+    let used: Vec<usize> = (0..count).filter(|&i| amounts[i] > 0).collect();
+    for property in &mut properties {
+        while used.iter().map(|&i| amounts[i] * property[i]).sum::<i64>() <= 0 {
+            let raisable: Vec<usize> = used.iter().copied().filter(|&i| property[i] < 5).collect();
+            property[*rng.choose(&raisable)] += 1;
+        }
+    }
 
+    let [capacity, durability, flavor, texture] = &properties;
+    let mut lines: Vec<String> = (0..count)
+        .map(|i| {
+            format!("{}: capacity {}, durability {}, flavor {}, texture {}, calories {}",
+                names[i], capacity[i], durability[i], flavor[i], texture[i], calories[i])
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
 
 
 #[cfg(test)]
@@ -180,8 +261,8 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let line = String::from("Sprinkles: capacity 5, durability -1, flavor 0, texture 0, calories 5");
-        let actual = parse_line(line);
+        let line = "Sprinkles: capacity 5, durability -1, flavor 0, texture 0, calories 5";
+        let actual = parse_line(1, line).unwrap();
         let expected = Ingredient {
             name: String::from("Sprinkles"),
             capacity: 5,
@@ -192,4 +273,29 @@ mod tests {
         };
         assert!(expected == actual);
     }
+
+    #[test]
+    fn test_algorithms() {
+        // No ingredient is the only one adding to its property, unlike the real ones
+        let ingredients: Vec<Ingredient> = [
+            "Frosting: capacity 4, durability -2, flavor 0, texture 0, calories 5",
+            "Candy: capacity 0, durability 5, flavor -1, texture 0, calories 8",
+            "Butterscotch: capacity -1, durability 0, flavor 5, texture 0, calories 6",
+            "Sugar: capacity 0, durability 0, flavor -2, texture 2, calories 1",
+        ].iter().map(|line| parse_line(1, line).unwrap()).collect();
+        assert_eq!((18965440, 15862900), best_scores_full_enumeration(&ingredients).unwrap());
+        assert_eq!((18965440, 15862900), best_scores_constrained(&ingredients).unwrap());
+    }
+
+    #[test]
+    fn test_generated_recipes_score() {
+        for size in 1..=MAX_INGREDIENTS {
+            for seed in 0..20 {
+                let input = PUZZLE.generator.generate(seed, Some(size));
+                let ingredients = Day15::parse(input.as_bytes().lines()).unwrap();
+                let (best, best_at_500_calories) = best_scores_constrained(&ingredients).unwrap();
+                assert!(best > 0 && best_at_500_calories > 0, "size {} seed {}:\n{}", size, seed, input);
+            }
+        }
+    }
 }
@@ -1,11 +1,55 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 
-use itertools::Itertools;
+use crate::error::{ PuzzleError, Result };
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
 
+pub struct Day16;
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 16,
+    title: "Aunt Sue",
+    tags: &[Tag::Search],
+    examples: &[],
+    generator: Generator { size: "number of aunts", default_size: 500, generate },
+    solver: &Solver::<Day16>::NEW,
+};
+
+
+pub type SueMap = HashMap<u32, HashMap<String, u32>>;
+
+impl Solution for Day16 {
+    type Input = SueMap;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<SueMap> {
+        let mut sue_map: SueMap = HashMap::new();
+
+        for (i, line) in input.enumerate() {
+            let (num, data) = parse_line(i+1, &line?)?;
+            sue_map.insert(num, data);
+        }
+
+        Ok(sue_map)
+    }
+
+    fn part1(sue_map: &SueMap, _params: &Params) -> Result<Answer> {
+        part_1(sue_map, &message())
+            .map(Answer::from)
+            .ok_or_else(|| PuzzleError::unsolvable("no Sue matches the message"))
+    }
+
+    fn part2(sue_map: &SueMap, _params: &Params) -> Result<Answer> {
+        part_2(sue_map, &message())
+            .map(Answer::from)
+            .ok_or_else(|| PuzzleError::unsolvable("no Sue matches the message"))
+    }
+}
+
+fn message() -> HashMap<String, u32> {
     let mut message: HashMap<String, u32> = HashMap::new();
     message.insert(String::from("children"), 3);
     message.insert(String::from("cats"), 7);
@@ -17,112 +61,120 @@ pub(crate) fn solve(input: Lines<BufReader<File>>) {
     message.insert(String::from("trees"), 3);
     message.insert(String::from("cars"), 2);
     message.insert(String::from("perfumes"), 1);
-    
-    let mut sue_map: HashMap<u32, HashMap<String, u32>> = HashMap::new();
-
-    for line in input {
-        let (num, data) = parse_line(line.unwrap());
-        sue_map.insert(num, data);
-    }
-
-    let part1 = part_1(&sue_map, &message);
-    let part2 = part_2(sue_map, &message);
-
-    println!("Part 1: The only Sue that matches is: Sue {}", part1);
-    println!("Part 2: The only Sue that matches is: Sue {}", part2);
+    message
 }
 
-fn part_1(sue_map: &HashMap<u32, HashMap<String, u32>>, message: &HashMap<String, u32>) -> u32 {
+fn part_1(sue_map: &SueMap, message: &HashMap<String, u32>) -> Option<u32> {
     for (sue_num, posessions_list) in sue_map {
         let mut is_match = true;
         for (item, count) in posessions_list {
-            if message.contains_key(item) {
-                let message_value = *message.get(item).unwrap();
-                if message_value != *count {
+            if let Some(message_value) = message.get(item) {
+                if message_value != count {
                     is_match = false;
                     break;
                 }
             }
         }
         if is_match {
-            return *sue_num;
+            return Some(*sue_num);
         }
     }
-    return 0;
+    None
 }
 
-fn part_2(sue_map: HashMap<u32, HashMap<String, u32>>, message: &HashMap<String, u32>) -> u32 {
+fn part_2(sue_map: &SueMap, message: &HashMap<String, u32>) -> Option<u32> {
     for (sue_num, posessions_list) in sue_map {
         let mut is_match = true;
 
         for (item, count) in posessions_list {
-            if message.contains_key(&item) {
-                let message_value = *message.get(&item).unwrap();
+            if let Some(message_value) = message.get(item) {
 
-                if item == String::from("cats") || item == String::from("trees") {
+                if item == "cats" || item == "trees" {
                     if message_value >= count {
                         is_match = false;
                         break;
                     }
 
-                } else if item == String::from("pomeranians") || item == String::from("goldfish") {
+                } else if item == "pomeranians" || item == "goldfish" {
                     if message_value <= count {
                         is_match = false;
                         break;
                     }
 
-                } else {
-                    if message_value != count {
-                        is_match = false;
-                        break;
-                    }
+                } else if message_value != count {
+                    is_match = false;
+                    break;
                 }
             }
         }
         if is_match {
-            return sue_num;
+            return Some(*sue_num);
         }
     }
-    return 0;
+    None
 }
 
-fn parse_line(line: String) -> (u32, HashMap<String, u32>) {
-    // get the number of the sue
-    let line = line.replace(":", "");
-    let line = line.replace(",", "");
+const SUE: Pattern = Pattern::new("Sue {number}: {compounds}");
+const COMPOUND: Pattern = Pattern::new("{compound}: {count}");
 
-    let s = line.split(" ").collect_vec();
-    let num = s[1].parse::<u32>().unwrap();
+fn parse_line(line_number: usize, line: &str) -> Result<(u32, HashMap<String, u32>)> {
+    // Example input: Sue 1: cars: 9, akitas: 3, goldfish: 0
+    let fields = SUE.parse(line_number, line)?;
+    let num = fields.number("number")?;
 
     let mut posessions: HashMap<String, u32> = HashMap::new();
-    for i in (2..s.len()).step_by(2) {
-        let key = String::from(s[i]);
-        let val = s[i+1].parse::<u32>().unwrap();
-        posessions.insert(key, val);
+    for compound in fields.list("compounds", ", ", &COMPOUND)? {
+        posessions.insert(String::from(compound.str("compound")), compound.number("count")?);
     }
 
-    return (num, posessions);
+    Ok((num, posessions))
 }
 
+/// Aunts with three random compounds each, exactly one of which matches the
+/// message in each part.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let message = message();
+    let mut compounds: Vec<&str> = message.keys().map(String::as_str).collect();
+    compounds.sort();
+
+    let size = size.max(2);
+    let part1_sue = rng.index(size) + 1;
+    let part2_sue = (part1_sue + rng.index(size - 1)) % size + 1;
+
+    let mut lines = Vec::with_capacity(size);
+    for sue in 1..=size {
+        let possessions = loop {
+            let mut chosen = compounds.clone();
+            rng.shuffle(&mut chosen);
+            chosen.truncate(3);
+
+            let possessions: Vec<(&str, u32)> = chosen.into_iter()
+                .map(|compound| {
+                    let expected = message[compound] as i64;
+                    let count = match compound {
+                        _ if sue == part1_sue => expected,
+                        "cats" | "trees" if sue == part2_sue => rng.range(expected+1..=expected+3),
+                        "pomeranians" | "goldfish" if sue == part2_sue => rng.range(0..=expected-1),
+                        _ if sue == part2_sue => expected,
+                        _ => rng.range(0..=10),
+                    };
+                    (compound, count as u32)
+                })
+                .collect();
+
+            let sue_map: SueMap = HashMap::from([
+                (1, possessions.iter().map(|&(compound, count)| (compound.to_string(), count)).collect())
+            ]);
+            if part_1(&sue_map, &message).is_some() == (sue == part1_sue)
+                && part_2(&sue_map, &message).is_some() == (sue == part2_sue) {
+                break possessions;
+            }
+        };
 
-
-
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[test]
-//     fn test_parse() {
-//         let line = String::from("Sprinkles: capacity 5, durability -1, flavor 0, texture 0, calories 5");
-//         let actual = parse_line(line);
-//         let expected = Ingredient {
-//             name: String::from("Sprinkles"),
-//             capacity: 5,
-//             durability: -1,
-//             flavor: 0,
-//             texture: 0,
-//             calories: 5,
-//         };
-//         assert!(expected == actual);
-//     }
-// }
+        let possessions: Vec<String> = possessions.iter()
+            .map(|(compound, count)| format!("{}: {}", compound, count))
+            .collect();
+        lines.push(format!("Sue {}: {}", sue, possessions.join(", ")));
+    }
+    lines.join("\n")
+}
@@ -1,106 +1,217 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-
-const TARGET_VOLUME: u32 = 150;
+use std::io::{ BufRead, Lines };
+
+use crate::cancel;
+use crate::error::{ self, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::input::Normalize;
+use crate::params::{ Param, Params };
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day17;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 17,
+    title: "No Such Thing as Too Much",
+    tags: &[Tag::Combinatorics],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of containers", default_size: 20, generate },
+    solver: &Solver::<Day17>::NEW,
+};
+
+/// Examples from the problem statement, which stores 25 liters.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_CONTAINERS, expected: "4", params: EXAMPLE_VOLUME },
+    Example { part: 2, input: EXAMPLE_CONTAINERS, expected: "3", params: EXAMPLE_VOLUME },
+];
+
+const EXAMPLE_CONTAINERS: &str = "20\n15\n10\n5\n5\n";
+
+const EXAMPLE_VOLUME: &[(&str, &str)] = &[("target-volume", "25")];
+
+impl Solution for Day17 {
+    type Input = Vec<u32>;
+
+    const PARAMS: &'static [Param] = &[
+        Param::integer("target-volume", "How many liters of eggnog the containers must hold", 150),
+        Param::algorithm("How to count the combinations: backtracking, or dynamic programming over volumes", &["backtracking", "dynamic-programming"]),
+    ];
+
+    // One number per line, so blank lines carry no meaning
+    const NORMALIZE: Normalize = Normalize { drop_blank_lines: true, ..Normalize::DEFAULT };
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<u32>> {
+        let mut containers: Vec<u32> = Vec::new();
+        for line in Self::NORMALIZE.lines(input) {
+            let (line_no, line) = line?;
+            let v = error::parse_number::<u32>(line_no, &line, line.trim())?;
+            containers.push(v);
+        }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-    // Recursive backtracking
+        // sort from largest to smallest
+        containers.sort();
+        containers.reverse();
 
-    let mut containers: Vec<u32> = Vec::new();
-    for line in input {
-        let v = line.unwrap().parse::<u32>().unwrap();
-        containers.push(v);
+        Ok(containers)
     }
 
-    // sort from largest to smallest
-    containers.sort();
-    containers.reverse();
-
-    let part1 = container_combinations(&containers, 0, -1);
-    let part2 = part_2(&containers);
+    fn part1(containers: &Vec<u32>, params: &Params) -> Result<Answer> {
+        let target = params.integer("target-volume");
+        let combos = match params.algorithm() {
+            "dynamic-programming" => combinations_by_count(containers, target).iter().sum(),
+            // Recursive backtracking
+            _ => u64::from(container_combinations(containers, target, 0, -1)?),
+        };
+        Ok(combos.into())
+    }
 
-    println!("Part 1: Combinations of containers: {}", part1);
-    println!("Part 2: {}", part2);
+    fn part2(containers: &Vec<u32>, params: &Params) -> Result<Answer> {
+        let target = params.integer("target-volume");
+        let combos = match params.algorithm() {
+            "dynamic-programming" => combinations_by_count(containers, target).into_iter().find(|&combos| combos > 0).unwrap_or(0),
+            _ => u64::from(part_2(containers, target)?),
+        };
+        Ok(combos.into())
+    }
 }
 
-
 fn container_combinations(
-    available_containers: &Vec<u32>,
+    available_containers: &[u32],
+    target: u32,
     running_total: u32,
-    last_container_used: i32) -> u32 {
+    last_container_used: i32) -> Result<u32> {
 
+    cancel::check()?;
     let start_index: usize = (last_container_used+1).try_into().unwrap();
     let mut combos = 0;
-    for i in start_index..available_containers.len() {
-        let e = available_containers[i];
+    for (i, &e) in available_containers.iter().enumerate().skip(start_index) {
         let v = e + running_total;
 
-        if v == TARGET_VOLUME {
+        if v == target {
             combos += 1;
-        } else if v < TARGET_VOLUME {
-            combos += container_combinations(available_containers, running_total+e, i.try_into().unwrap())
+        } else if v < target {
+            combos += container_combinations(available_containers, target, running_total+e, i.try_into().unwrap())?
         }
     }
 
-    combos
+    Ok(combos)
 }
 
-fn part_2(available_containers: &Vec<u32>) -> u32 {
-    let n = minimum_number_of_containers(available_containers, 0, -1);
-    return n_size_combos_that_sum_to_target(available_containers, 0, -1, 0, n);
+fn part_2(available_containers: &[u32], target: u32) -> Result<u32> {
+    let n = minimum_number_of_containers(available_containers, target, 0, -1)?;
+    n_size_combos_that_sum_to_target(available_containers, target, 0, -1, 0, n)
 }
 
 fn minimum_number_of_containers(
-    available_containers: &Vec<u32>,
+    available_containers: &[u32],
+    target: u32,
     running_total: u32,
-    last_container_used: i32) -> u32 {
+    last_container_used: i32) -> Result<u32> {
 
+    cancel::check()?;
+    // The first combination found is not always the smallest, so every branch has to be tried
     let start_index: usize = (last_container_used+1).try_into().unwrap();
-    for i in start_index..available_containers.len() {
-        let e = available_containers[i];
+    let mut minimum = 0;
+    for (i, &e) in available_containers.iter().enumerate().skip(start_index) {
         let v = e + running_total;
 
-        if v == TARGET_VOLUME {
-            return 1;
-        } else if v < TARGET_VOLUME {
-            let recurse = minimum_number_of_containers(available_containers, v, i.try_into().unwrap());
-            if recurse > 0 {
-                return recurse + 1;
+        if v == target {
+            return Ok(1);
+        } else if v < target {
+            let recurse = minimum_number_of_containers(available_containers, target, v, i.try_into().unwrap())?;
+            if recurse > 0 && (minimum == 0 || recurse + 1 < minimum) {
+                minimum = recurse + 1;
             }
         }
     }
-    0
+    Ok(minimum)
 }
 
 fn n_size_combos_that_sum_to_target(
-    available_containers: &Vec<u32>,
+    available_containers: &[u32],
+    target: u32,
     running_total: u32,
     last_container_used: i32,
     num_containers_used: u32,
-    n: u32) -> u32 {
+    n: u32) -> Result<u32> {
 
+    cancel::check()?;
     if num_containers_used >= n {
-        return 0;
+        return Ok(0);
     }
 
     let mut combos = 0;
     let start_index: usize = (last_container_used+1).try_into().unwrap();
-    for i in start_index..available_containers.len() {
-        let e = available_containers[i];
+    for (i, &e) in available_containers.iter().enumerate().skip(start_index) {
         let v = e + running_total;
 
-        if v == TARGET_VOLUME {
+        if v == target {
             combos += 1;
-        } else if v < TARGET_VOLUME {
+        } else if v < target {
             combos += n_size_combos_that_sum_to_target(
                 available_containers, 
+                target,
                 v, 
                 i.try_into().unwrap(), 
-                num_containers_used+1, n);
+                num_containers_used+1, n)?;
         }
 
     }
     
-    combos
+    Ok(combos)
+}
+
+/// How many combinations of containers hold exactly `target` liters, indexed by the
+/// number of containers used, less one.
+fn combinations_by_count(available_containers: &[u32], target: u32) -> Vec<u64> {
+    let target = target as usize;
+    let n = available_containers.len();
+
+    // ways[count][volume] is how many combinations of `count` of the containers
+    // seen so far hold `volume` liters
+    let mut ways = vec![vec![0u64; target + 1]; n + 1];
+    ways[0][0] = 1;
+    for (seen, &container) in available_containers.iter().enumerate() {
+        let container = container as usize;
+        if container > target {
+            continue;
+        }
+        // Go backwards so that each container is used at most once
+        for count in (0..=seen).rev() {
+            for volume in (0..=target - container).rev() {
+                ways[count + 1][volume + container] += ways[count][volume];
+            }
+        }
+    }
+
+    ways.iter().skip(1).map(|by_volume| by_volume[target]).collect()
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| rng.range(5..=50).to_string()).collect::<Vec<_>>().join("\n")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PuzzleError;
+
+    #[test]
+    fn test_minimum_is_not_the_first_combination_found() {
+        // Starting from the largest container finds 6 + 2 + 2 before 5 + 5
+        let containers = Day17::parse("6\n5\n5\n2\n2".as_bytes().lines()).unwrap();
+        assert_eq!(2, minimum_number_of_containers(&containers, 10, 0, -1).unwrap());
+        assert_eq!(1, part_2(&containers, 10).unwrap());
+    }
+
+    #[test]
+    fn test_blank_lines_keep_line_numbers() {
+        assert_eq!(vec![20, 15], Day17::parse("20\n\n15\n".as_bytes().lines()).unwrap());
+        match Day17::parse("20\n\n  \nfive".as_bytes().lines()) {
+            Err(PuzzleError::Parse { line, .. }) => assert_eq!(4, line),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
 }
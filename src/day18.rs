@@ -1,174 +1,190 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
 use std::fmt;
 
 use itertools::Itertools;
 
-const OFF: u32 = 0;
-const ON: u32 = 1;
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::grid::{ Grid, Point };
+use crate::params::{ Param, Params };
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ self, Answer, Query, QueryError, Solution, Visual };
+
+pub struct Day18;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 18,
+    title: "Like a GIF For Your Yard",
+    tags: &[Tag::Grid, Tag::Simulation],
+    examples: EXAMPLES,
+    generator: Generator { size: "width and height of the grid", default_size: 100, generate },
+    solver: &Solver::<Day18>::NEW,
+};
+
+/// Examples from the problem statement, which animates a 6x6 grid for four steps,
+/// or five with the corners stuck on.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_GRID, expected: "4", params: &[("steps", "4")] },
+    Example { part: 2, input: EXAMPLE_GRID, expected: "17", params: &[("steps", "5")] },
+];
+
+const EXAMPLE_GRID: &str = ".#.#.#\n...##.\n#....#\n..#...\n#.#..#\n####..\n";
 
 #[derive(Clone)]
-struct Field {
-    width: usize,
-    height: usize,
-    data: Vec<u32>,
+pub struct Field {
+    lights: Grid<bool>,
 }
 
 impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut rows: Vec<String> = Vec::new();
-
-        for i in 0..self.height {
-            let s = i*self.width;
-            let e = i*self.width + self.width;
-            let row = &self.data[s..e];
-            let row: Vec<&str> = row.iter().map(|x| match *x {
-                OFF => ".",
-                ON => "#",
-                _ => panic!("Unrecognized value in row"),
-            }).collect();
-            rows.push(row.join(""));
-        }
+        write!(f, "{}", self.lights.render(|&on| if on { '#' } else { '.' }))
+    }
+}
 
-        write!(f, "{}", rows.join("\n"))
+/// Shows the lights as rows of `#` and `.`, like the input, rather than as a list of flags.
+impl fmt::Debug for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = self.to_string();
+        let rows: Vec<&str> = text.lines().collect();
+        f.debug_struct("Field")
+            .field("width", &self.lights.width())
+            .field("height", &self.lights.height())
+            .field("rows", &rows)
+            .finish()
     }
 }
 
 impl Field {
-    fn sum_of_neighbors(&self, i: usize) -> u32 {
-        let row = i / self.width;
-        let col = i % self.width;
-
-        let is_left_edge = col == 0;
-        let is_right_edge = col == self.width-1;
-        let is_top_edge = row == 0;
-        let is_bottom_edge = row == self.height-1;
-
-        let mut ttl = 0;
-
-        // left
-        if !is_left_edge {
-            ttl += self.data[i-1];
-
-            // upper left diagonal
-            if !is_top_edge {
-                ttl += self.data[i-1-self.width];
-            }
-            // lower left diagonal
-            if !is_bottom_edge {
-                ttl += self.data[i-1+self.width];
-            }
-        }
-        // right
-        if !is_right_edge {
-            ttl += self.data[i+1];
-
-            // upper right diagonal
-            if !is_top_edge {
-                ttl += self.data[i+1-self.width];
-            }
-            // bottom right diagonal
-            if !is_bottom_edge {
-                ttl += self.data[i+1+self.width];
-            }
-        }
-        // above
-        if !is_top_edge {
-            ttl += self.data[i-self.width];
-        }
-        // below
-        if !is_bottom_edge {
-            ttl += self.data[i+self.width];
-        }
-        
-        ttl
+    fn sum_of_neighbors(&self, p: Point) -> u32 {
+        self.lights.neighbors8(p).filter(|&(_, &on)| on).count() as u32
     }
 
     fn count_lights_on(&self) -> u32 {
-        self.data.iter().sum()
+        self.lights.values().filter(|&&on| on).count() as u32
     }
 
     fn iterate(&mut self) {
-        let size = self.width*self.height;
-        let sums_of_neighbors = (0..size).map(|x| self.sum_of_neighbors(x)).collect_vec();
-
-        for i in 0..size {
-            let sum_of_neighbors = sums_of_neighbors[i];
-            let value = self.data[i];
-            if value == ON {
-                if sum_of_neighbors != 2 && sum_of_neighbors != 3 {
-                    self.data[i] = OFF;
-                }
-            } else if value == OFF {
-                if sum_of_neighbors == 3 {
-                    self.data[i] = ON;
-                }
-            }
+        let sums_of_neighbors = self.lights.bounds().points().map(|p| self.sum_of_neighbors(p)).collect_vec();
+
+        for (on, sum_of_neighbors) in self.lights.values_mut().zip(sums_of_neighbors) {
+            *on = matches!((*on, sum_of_neighbors), (true, 2) | (_, 3));
         }
     }
 
     fn fix_corners(&mut self) {
-        self.data[0] = ON;
-        self.data[self.width-1] = ON;
-
-        let len = self.data.len();
-        let bottom_left = len - self.width;
-        self.data[bottom_left] = ON;
-
-        self.data[len-1] = ON;
+        let bounds = self.lights.bounds();
+        for corner in [bounds.min, Point::new(bounds.max.x, 0), Point::new(0, bounds.max.y), bounds.max] {
+            self.lights[corner] = true;
+        }
     }
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+impl Solution for Day18 {
+    type Input = Field;
 
-    let mut field1 = parse_input(input);
+    const PARAMS: &'static [Param] = &[
+        Param::integer("steps", "How many steps to animate the lights for", 100),
+        Param::flag("stuck-corners", "Whether the four corner lights are stuck on in part 2", true),
+    ];
 
-    let mut field2 = field1.clone();
-    field2.fix_corners();
-    
-    for _ in 0..100 {
-        field1.iterate();
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Field> {
+        parse_input(input)
+    }
 
-        field2.iterate();
-        field2.fix_corners();
+    fn part1(field: &Field, params: &Params) -> Result<Answer> {
+        Ok(part_1_field(field, params).count_lights_on().into())
     }
 
-    let part1 = field1.count_lights_on();
-    let part2 = field2.count_lights_on();
+    fn part2(field: &Field, params: &Params) -> Result<Answer> {
+        Ok(part_2_field(field, params).count_lights_on().into())
+    }
 
-    println!("Part 1: Total lights on after 100 iterations: {}", part1);
-    println!("Part 2: Total lights on after 100 iterations: {}", part2);
+    fn visualize(field: &Field, params: &Params) -> Result<Vec<Visual>> {
+        let steps = params.integer("steps");
+        Ok(vec![
+            Visual::new("Initial lights", field.to_string()),
+            Visual::new(format!("Lights after {} steps in part 1", steps), part_1_field(field, params).to_string()),
+            Visual::new(format!("Lights after {} steps in part 2", steps), part_2_field(field, params).to_string()),
+        ])
+    }
+
+    const QUERIES: &'static [Query] = &[
+        Query { name: "gen", args: "<steps> [part]", help: "The lights after this many steps, with the rules of part 1 or 2" },
+    ];
+
+    fn query(field: &Field, params: &Params, name: &str, args: &[&str]) -> std::result::Result<String, QueryError> {
+        match name {
+            "gen" => {
+                let steps: u32 = solution::query_argument(args, 0, "number of steps")?;
+                let part: u8 = if args.len() > 1 { solution::query_argument(args, 1, "part")? } else { 1 };
+                let field = match part {
+                    1 => animate(field, steps, false),
+                    2 => animate(field, steps, params.flag("stuck-corners")),
+                    _ => return Err(QueryError::Usage(format!("there is no part {}", part))),
+                };
+                Ok(format!("{} lights on after {} steps\n{}", field.count_lights_on(), steps, field))
+            },
+            _ => Err(QueryError::UnknownCommand(name.to_string())),
+        }
+    }
 }
 
-fn parse_input(input: Lines<BufReader<File>>) -> Field {
-    let mut width = 0;
-    let mut height: usize = 0;
-    let mut data: Vec<u32> = Vec::new();
+fn part_1_field(field: &Field, params: &Params) -> Field {
+    animate(field, params.integer("steps"), false)
+}
 
-    for line in input {
-        let v = line.unwrap();
+fn part_2_field(field: &Field, params: &Params) -> Field {
+    animate(field, params.integer("steps"), params.flag("stuck-corners"))
+}
 
-        if width == 0 {
-            width = v.len();
-        }
-        height += 1;
-
-        for c in v.chars() {
-            match c {
-                '#' => data.push(ON),
-                '.' => data.push(OFF),
-                _ => panic!("Unexpected char {}", c),
-            }
+/// The lights after `steps` steps, with the four corners stuck on if `stuck_corners` is set.
+fn animate(field: &Field, steps: u32, stuck_corners: bool) -> Field {
+    let mut field = field.clone();
+    if stuck_corners {
+        field.fix_corners();
+    }
+    for _ in 0..steps {
+        field.iterate();
+        if stuck_corners {
+            field.fix_corners();
         }
     }
+    field
+}
+
+fn parse_input<R: BufRead>(input: Lines<R>) -> Result<Field> {
+    let lights = Grid::parse(input, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    }, "'#' or '.'")?;
+    Ok(Field { lights })
+}
 
-    return Field {width, height, data}
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    (0..size)
+        .map(|_| (0..size).map(|_| if rng.chance(1, 2) { '#' } else { '.' }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A field from rows of 1s for lights that are on and 0s for lights that are off.
+    fn field(width: usize, height: usize, data: Vec<u32>) -> Field {
+        Field { lights: Grid::from_cells(width, height, data.into_iter().map(|light| light == 1).collect()) }
+    }
+
+    /// The position of the `i`th light, counting a row at a time.
+    fn at(width: usize, i: usize) -> Point {
+        Point::new((i % width) as i64, (i / width) as i64)
+    }
+
     #[test]
     fn test_sum_of_neighbors_empty_board() {
         let data = vec![
@@ -179,9 +195,9 @@ mod tests {
         ];
         let width = 3;
         let height = 4;
-        let field = Field{width, height, data};
+        let field = field(width, height, data);
         for i in 0..(width*height) {
-            assert_eq!(0, field.sum_of_neighbors(i));
+            assert_eq!(0, field.sum_of_neighbors(at(width, i)));
         }
     }
 
@@ -195,7 +211,7 @@ mod tests {
         ];
         let width = 3;
         let height = 4;
-        let field = Field{width, height, data};
+        let field = field(width, height, data);
 
         let expected = vec![
             0, 1, 0,
@@ -204,8 +220,8 @@ mod tests {
             0, 0, 0,
         ];
 
-        for i in 0..(width*height) {
-            assert_eq!(expected[i], field.sum_of_neighbors(i));
+        for (i, expected) in expected.into_iter().enumerate() {
+            assert_eq!(expected, field.sum_of_neighbors(at(width, i)));
         }
     }
 
@@ -219,7 +235,7 @@ mod tests {
         ];
         let width = 3;
         let height = 4;
-        let field = Field{width, height, data};
+        let field = field(width, height, data);
 
         let expected = vec![
             0, 2, 1,
@@ -228,8 +244,8 @@ mod tests {
             2, 1, 2,
         ];
 
-        for i in 0..(width*height) {
-            assert_eq!(expected[i], field.sum_of_neighbors(i));
+        for (i, expected) in expected.into_iter().enumerate() {
+            assert_eq!(expected, field.sum_of_neighbors(at(width, i)));
         }
     }
 
@@ -243,7 +259,7 @@ mod tests {
         ];
         let width = 3;
         let height = 4;
-        let field = Field{width, height, data};
+        let field = field(width, height, data);
         assert_eq!(4, field.count_lights_on());
     }
 
@@ -260,7 +276,7 @@ mod tests {
         ];
         let width = 6;
         let height = 6;
-        let mut field = Field{width, height, data};
+        let mut field = field(width, height, data);
         
         assert_eq!(15, field.count_lights_on());
         
@@ -291,7 +307,7 @@ mod tests {
         ];
         let width = 6;
         let height = 6;
-        let mut field = Field{width, height, data};
+        let mut field = field(width, height, data);
         field.fix_corners();
         println!("Initial state: \n{}\n", field);
         
@@ -318,4 +334,12 @@ mod tests {
         field.fix_corners();
         assert_eq!(17, field.count_lights_on());
     }
+    #[test]
+    fn test_corners_can_come_unstuck() {
+        let field = Day18::parse(EXAMPLE_GRID.as_bytes().lines()).unwrap();
+        let mut params = Params::new(Day18::PARAMS);
+        params.set("steps", "4").unwrap();
+        params.set("stuck-corners", "false").unwrap();
+        assert_eq!(Answer::Number(4), Day18::part2(&field, &params).unwrap());
+    }
 }
@@ -1,92 +1,189 @@
-use std::collections::{HashMap, HashSet, BinaryHeap};
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::collections::{ HashMap, HashSet };
+use std::hash::{ Hash, Hasher };
+use std::io::{ BufRead, Lines };
+
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::search::{ self, State };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day19;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 19,
+    title: "Medicine for Rudolph",
+    tags: &[Tag::Strings, Tag::Search],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of replacements that make the molecule", default_size: 20, generate },
+    solver: &Solver::<Day19>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "H => HO\nH => OH\nO => HH\n\nHOH", expected: "4", params: &[] },
+    Example { part: 1, input: "H => HO\nH => OH\nO => HH\n\nHOHOHO", expected: "7", params: &[] },
+    Example { part: 2, input: "e => H\ne => O\nH => HO\nH => OH\nO => HH\n\nHOH", expected: "3", params: &[] },
+    Example { part: 2, input: "e => H\ne => O\nH => HO\nH => OH\nO => HH\n\nHOHOHO", expected: "6", params: &[] },
+];
+
+pub type ReplacementMap = HashMap<String, Vec<String>>;
+
+impl Solution for Day19 {
+    type Input = (ReplacementMap, String);
+
+    const PARAMS: &'static [Param] = &[
+        Param::algorithm(
+            "How to search for the fewest steps: greedily, which is exact when every way of making the molecule takes as many steps, as with the real inputs, and falls back to a-star for other inputs, or a-star, which is exact for any input",
+            &["greedy", "a-star"],
+        ),
+    ];
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<(ReplacementMap, String)> {
+        parse_input(input)
+    }
 
-type ReplacementMap = HashMap<String, Vec<String>>;
+    fn part1((replacements, target_molecule): &(ReplacementMap, String), _params: &Params) -> Result<Answer> {
+        Ok(part_1(replacements, target_molecule).into())
+    }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+    fn part2((replacements, target_molecule): &(ReplacementMap, String), params: &Params) -> Result<Answer> {
+        let steps = part_2(replacements, target_molecule, params.algorithm())?
+            .ok_or_else(|| PuzzleError::unsolvable(format!("{} cannot be made starting from e", target_molecule)))?;
+        Ok(steps.into())
+    }
+}
 
-    let (replacements, target_molecule) = parse_input(input);
+/// A molecule partway through being taken back down to `e`, one replacement
+/// undone at a time. Molecules are the same if their text is.
+#[derive(Debug, Clone)]
+struct Molecule<'a> {
+    text: String,
+    /// The elements each right-hand side can be made from
+    inverse: &'a ReplacementMap,
+    /// What the heuristic divides the molecule's length by: the most that undoing
+    /// one replacement can shorten it by, so that a-star never overestimates, or 1
+    /// for greedy, which only compares lengths and does better with fewer ties
+    shrink: usize,
+}
 
-    let part1 = part_1(&replacements, target_molecule.clone());
-    println!("Part 1: Number of distinct molecules that can be created: {}", part1);
+impl PartialEq for Molecule<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
 
+impl Eq for Molecule<'_> {}
 
-    let part2 = part_2(&replacements, target_molecule);
-    println!("Part 2: Steps to create target molecule: {}", part2);
+impl Hash for Molecule<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.text.hash(state);
+    }
+}
+
+impl State for Molecule<'_> {
+    type Cost = u32;
+
+    fn neighbors(&self) -> Vec<(Self, u32)> {
+        // Sorted so that the search takes the same path every time
+        let mut texts: Vec<String> = get_neighbors(self.inverse, &self.text).into_iter().collect();
+        texts.sort_unstable();
+        texts.into_iter()
+            .map(|text| (Molecule { text, inverse: self.inverse, shrink: self.shrink }, 1))
+            .collect()
+    }
+
+    fn is_goal(&self) -> bool {
+        self.text == "e"
+    }
+
+    fn heuristic(&self) -> u32 {
+        // The shorter a molecule is, the closer it is to just being 'e'
+        if self.shrink == 0 {
+            return 0;
+        }
+        (self.text.len() - 1).div_ceil(self.shrink) as u32
+    }
 }
 
-fn part_2(replacements_map: &ReplacementMap, target_molecule: String) -> u32 {
+fn part_2(replacements_map: &ReplacementMap, target_molecule: &str, algorithm: &str) -> Result<Option<u32>> {
 
     // For any given element, there are multiple possibilities for what it can
     // be replaced into. However, the right hand side of each replacement operation
     // is unique. That means that figuring out which replacements can be done in
     // reverse order (i.e., starting at the target molecule and working our way back
     // down to the starting electron 'e', requires far less branching).
-    // This approach also provides us with a very handy rule for calculating priorities:
-    // The shorter a node is, the closer it is to just being 'e'
     let mut irm: ReplacementMap = HashMap::new();
     for (key, value) in replacements_map {
         for s in value {
-            if irm.contains_key(s) {
-                let v = irm.get_mut(s).unwrap();
-                v.push(key.clone());
-            } else {
-                irm.insert(s.clone(), vec!(key.clone()));
-            }
+            irm.entry(s.clone()).or_default().push(key.clone());
         }
     }
 
-
-    return dijkstra(&irm, target_molecule, String::from("e"));
+    // Greedy can find a longer way than the fewest steps, unless there is no shorter one
+    let algorithm = match algorithm {
+        "greedy" if !every_way_takes_as_many_steps(replacements_map) => "a-star",
+        algorithm => algorithm,
+    };
+    fewest_steps(&irm, target_molecule, algorithm)
 }
 
-fn dijkstra(replacements_map: &ReplacementMap, start_node: String, target_node: String) -> u32 {
-    if start_node == target_node {
-        return 0;
-    }
-
-    let mut visited: HashSet<String> = HashSet::new();
-
-    let mut queue: BinaryHeap<(i32, String, u32)> = BinaryHeap::new();
-    // (priority, node, distance from start_node)
-    queue.push((0, start_node.clone(), 0));
-
-    while queue.len() > 0 {
-        let (_, current_node, current_depth) = queue.pop().unwrap();
-
-        let neighbors = get_neighbors(replacements_map, &current_node);
-        let next_depth = current_depth + 1;
-        for neighbor in neighbors {
-            if neighbor == target_node {
-                return next_depth;
-            }
-            if visited.contains(&neighbor) {
-                // this node can be reached through a faster path
-                continue;
-            }
-            visited.insert(neighbor.clone());
+/// Whether every way of making a molecule takes the same number of steps, because
+/// each replacement adds one to its [`weight`] and those from `e` all make
+/// molecules of the same weight.
+fn every_way_takes_as_many_steps(replacements_map: &ReplacementMap) -> bool {
+    let mut from_e = None;
+    replacements_map.iter().all(|(lhs, rhs)| rhs.iter().all(|rhs| match lhs.as_str() {
+        "e" => *from_e.get_or_insert(weight(rhs)) == weight(rhs),
+        _ => weight(rhs) == weight(lhs) + 1,
+    }))
+}
 
-            // Negate the priority so smaller edit distances get higher priority
-            let priority = -1 * (neighbor.len() as i32);
-            queue.push((priority, neighbor, next_depth));
-        }
+/// The number of elements in a molecule, where `Rn` and `Ar` count for nothing and
+/// `Y` takes one away. In the real inputs they only appear as `XRnYAr`, `XRnYYZAr`
+/// and so on, like brackets and commas, so that every replacement adds one.
+fn weight(molecule: &str) -> i64 {
+    let starts = molecule.char_indices()
+        .filter(|(_, c)| !c.is_ascii_lowercase() || *c == 'e')
+        .map(|(i, _)| i)
+        .chain([molecule.len()])
+        .collect::<Vec<_>>();
+    starts.windows(2)
+        .map(|element| match &molecule[element[0]..element[1]] {
+            "Rn" | "Ar" => 0,
+            "Y" => -1,
+            _ => 1,
+        })
+        .sum()
+}
 
-    }
-    
-    panic!("No solution found starting from {} to reach {}", start_node, target_node);
+/// The fewest replacements that, undone with `inverse`, take `molecule` back to `e`.
+fn fewest_steps(inverse: &ReplacementMap, molecule: &str, algorithm: &str) -> Result<Option<u32>> {
+    let path = match algorithm {
+        "a-star" => {
+            let shrink = inverse.iter()
+                .flat_map(|(rhs, lhs)| lhs.iter().map(move |lhs| rhs.len().saturating_sub(lhs.len())))
+                .max()
+                .unwrap_or(0);
+            search::astar(Molecule { text: molecule.to_string(), inverse, shrink })?
+        },
+        _ => search::greedy(Molecule { text: molecule.to_string(), inverse, shrink: 1 })?,
+    };
+    Ok(path.map(|path| path.cost))
 }
 
-fn part_1(replacements_map: &ReplacementMap, target_molecule: String) -> usize {
+fn part_1(replacements_map: &ReplacementMap, target_molecule: &str) -> usize {
     // brute force.
     // For each possible element, find the index of every occurence in the molecule.
     // Then, apply each possible replacement at each index where it occurs.
 
-    let results = get_neighbors(replacements_map, &target_molecule);    
-    return results.len();
+    let results = get_neighbors(replacements_map, target_molecule);    
+    results.len()
 }
 
-fn get_neighbors(replacements_map: &ReplacementMap, node: &String) -> HashSet<String> {
+fn get_neighbors(replacements_map: &ReplacementMap, node: &str) -> HashSet<String> {
     let mut results: HashSet<String> = HashSet::new();
 
     for (key, replacements_list) in replacements_map {
@@ -97,16 +194,16 @@ fn get_neighbors(replacements_map: &ReplacementMap, node: &String) -> HashSet<St
             for replacement in replacements_list {
                 
                 let rng = index..index+key_length;
-                let mut replaced = node.clone();
+                let mut replaced = node.to_string();
                 replaced.replace_range(rng, replacement);
                 results.insert(replaced);
             }
         }
     }
-    return results;
+    results
 }
 
-fn parse_input(input: Lines<BufReader<File>>) -> (ReplacementMap, String) {
+fn parse_input<R: BufRead>(input: Lines<R>) -> Result<(ReplacementMap, String)> {
     // Group the possible replacements by the element on the left-hand side.
     //
     // Example: If the list of replacements contains these possible replacements
@@ -117,25 +214,77 @@ fn parse_input(input: Lines<BufReader<File>>) -> (ReplacementMap, String) {
     let mut replacements: ReplacementMap = HashMap::new();
     let mut target_molecule = String::new();
 
-    for line in input {
-        let v = line.unwrap();
+    let mut line_count = 0;
+    for (i, line) in input.enumerate() {
+        let v = line?;
+        line_count = i+1;
+
+        if v.contains(" => ") {
+            let split: Vec<&str> = v.split(" => ").collect();
+            if split.len() != 2 || split[0].is_empty() || split[1].is_empty() {
+                return Err(PuzzleError::invalid_line(i+1, &v, "expected a replacement like 'H => HO'"));
+            }
+            let lhs = String::from(split[0]);
+            let rhs = String::from(split[1]);
+
+            let v = replacements.entry(lhs).or_default();
+            v.push(rhs);
+        } else {
+            target_molecule = v;
+        }
+    }
 
-        match v.contains(" => ") {
-            true => {
-                let split: Vec<&str> = v.split(" => ").collect();
-                let lhs = String::from(split[0]);
-                let rhs = String::from(split[1]);
+    if target_molecule.is_empty() {
+        return Err(PuzzleError::invalid_line(line_count+1, "", "expected the medicine molecule after the replacements"));
+    }
+
+    Ok((replacements, target_molecule))
+}
+
+const ELEMENTS: &[&str] = &["Al", "B", "Ca", "F", "H", "Mg", "N", "O", "P", "Si", "Th", "Ti"];
+
+/// Replacements shaped like the real ones, where an element becomes two elements or
+/// a group like `ThRnFAr` or `CaRnFYFAr`, so that every way of making the molecule
+/// takes the same number of steps. The molecule is made from `e` in `size` steps.
+/// A real molecule takes about 200 steps, too many for a-star, so the default is 20.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut rules: Vec<(&str, Vec<&str>)> = Vec::new();
+    for &element in ["e"].iter().chain(ELEMENTS) {
+        for _ in 0..rng.range(2..=4) {
+            let rhs = match rng.index(5) {
+                _ if element == "e" => vec![*rng.choose(ELEMENTS), *rng.choose(ELEMENTS)],
+                0 => vec![*rng.choose(ELEMENTS), "Rn", *rng.choose(ELEMENTS), "Ar"],
+                1 => vec![*rng.choose(ELEMENTS), "Rn", *rng.choose(ELEMENTS), "Y", *rng.choose(ELEMENTS), "Ar"],
+                _ => vec![*rng.choose(ELEMENTS), *rng.choose(ELEMENTS)],
+            };
+            // Every molecule on the right-hand side must be unique
+            if rules.iter().all(|(_, other)| *other != rhs) {
+                rules.push((element, rhs));
+            }
+        }
+    }
 
-                let v = replacements.entry(lhs).or_insert_with(|| Vec::new());
-                v.push(rhs);
-            },
-            false => target_molecule = v,
+    let mut molecule = vec!["e"];
+    for _ in 0..size.max(1) {
+        let expandable: Vec<usize> = (0..molecule.len())
+            .filter(|&i| rules.iter().any(|(lhs, _)| *lhs == molecule[i]))
+            .collect();
+        if expandable.is_empty() {
+            break;
         }
+        let i = *rng.choose(&expandable);
+        let choices: Vec<&Vec<&str>> = rules.iter().filter(|(lhs, _)| *lhs == molecule[i]).map(|(_, rhs)| rhs).collect();
+        let rhs = rng.choose(&choices);
+        molecule.splice(i..=i, rhs.iter().copied());
     }
 
-    return (replacements, target_molecule);
+    let mut lines: Vec<String> = rules.iter().map(|(lhs, rhs)| format!("{} => {}", lhs, rhs.concat())).collect();
+    lines.push(String::new());
+    lines.push(molecule.concat());
+    lines.join("\n")
 }
 
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,26 +324,55 @@ mod tests {
     }
 
     #[test]
-    fn test_bfs() {
-        let h = String::from("H");
-        let o = String::from("O");
-        let e = String::from("e");
-        let ho = String::from("HO");
-        let oh = String::from("OH");
-        let hh = String::from("HH");
-        
-        let mut replacements_map: ReplacementMap = HashMap::new();
-        replacements_map.insert(e.clone(), vec!(h.clone(), o.clone()));
-        replacements_map.insert(h.clone(), vec!(oh, ho));
-        replacements_map.insert(o.clone(), vec!(hh.clone()));
+    fn test_fewest_steps() {
+        // The replacements from the problem statement, the other way around
+        let mut inverse: ReplacementMap = HashMap::new();
+        for (rhs, lhs) in [("H", "e"), ("O", "e"), ("HO", "H"), ("OH", "H"), ("HH", "O")] {
+            inverse.entry(String::from(rhs)).or_default().push(String::from(lhs));
+        }
+
+        for algorithm in ["greedy", "a-star"] {
+            assert_eq!(Some(0), fewest_steps(&inverse, "e", algorithm).unwrap());
+            assert_eq!(Some(3), fewest_steps(&inverse, "HOH", algorithm).unwrap());
+            assert_eq!(Some(6), fewest_steps(&inverse, "HOHOHO", algorithm).unwrap());
+            assert_eq!(None, fewest_steps(&inverse, "HNH", algorithm).unwrap());
+        }
+    }
 
-        let distance = dijkstra(&replacements_map, e.clone(), e.clone());
-        assert_eq!(0, distance);
+    #[test]
+    fn test_weight() {
+        assert_eq!(1, weight("e"));
+        assert_eq!(3, weight("HOH"));
+        assert_eq!(2, weight("CaRnFAr"));
+        assert_eq!(2, weight("ThRnFYFAr"));
+        assert_eq!(2, weight("SiRnMgYFYCaAr"));
+    }
 
-        let distance = dijkstra(&replacements_map, e.clone(), String::from("HOH"));
-        assert_eq!(3, distance);
+    #[test]
+    fn test_greedy_falls_back_to_a_star() {
+        // Undoing X => AB shortens the molecule the most, but X is three steps from e
+        let (replacements, molecule) = parse_input(concat!(
+            "e => PQ\nP => A\nQ => B\n",
+            "e => F\nF => N\nN => X\nX => AB\n\nAB",
+        ).as_bytes().lines()).unwrap();
+        assert!(!every_way_takes_as_many_steps(&replacements));
+
+        let mut inverse: ReplacementMap = HashMap::new();
+        for (lhs, rhs) in &replacements {
+            for rhs in rhs {
+                inverse.entry(rhs.clone()).or_default().push(lhs.clone());
+            }
+        }
+        assert_eq!(Some(4), fewest_steps(&inverse, &molecule, "greedy").unwrap());
+        assert_eq!(Some(3), part_2(&replacements, &molecule, "greedy").unwrap());
+    }
 
-        let distance = dijkstra(&replacements_map, e.clone(), String::from("HOHOHO"));
-        assert_eq!(6, distance);
+    #[test]
+    fn test_generated_inputs_are_greedy() {
+        for seed in 0..10 {
+            let input = PUZZLE.generator.generate(seed, None);
+            let (replacements, _) = Day19::parse(input.as_bytes().lines()).unwrap();
+            assert!(every_way_takes_as_many_steps(&replacements), "seed {}:\n{}", seed, input);
+        }
     }
 }
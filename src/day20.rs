@@ -1,23 +1,53 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-
-    let puzzle_input = parse_input(input);
-
-    let part1 = part_1(puzzle_input);
-    println!("Part 1: {}", part1);
+use std::io::{ BufRead, Lines };
+
+use crate::cancel;
+use crate::error::{ self, PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day20;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 20,
+    title: "Infinite Elves and Infinite Houses",
+    tags: &[Tag::Math, Tag::BruteForce],
+    examples: EXAMPLES,
+    generator: Generator { size: "target in thousands of presents", default_size: 1000, generate },
+    solver: &Solver::<Day20>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "10", expected: "1", params: &[] },
+    Example { part: 1, input: "70", expected: "4", params: &[] },
+    Example { part: 1, input: "120", expected: "6", params: &[] },
+    Example { part: 1, input: "150", expected: "8", params: &[] },
+];
+
+impl Solution for Day20 {
+    type Input = u32;
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<u32> {
+        parse_input(input)
+    }
 
+    fn part1(puzzle_input: &u32, _params: &Params) -> Result<Answer> {
+        Ok(part_1(*puzzle_input)?.into())
+    }
 
-    let part2 = part_2(puzzle_input);
-    println!("Part 2: {}", part2);
+    fn part2(puzzle_input: &u32, _params: &Params) -> Result<Answer> {
+        Ok(part_2(*puzzle_input)?.into())
+    }
 }
 
-fn part_2(puzzle_input: u32) -> u32 {
+fn part_2(puzzle_input: u32) -> Result<u32> {
     let mut house = 1;
     let packages_per_elf = 11;
     loop {
+        cancel::check()?;
         let mut count_of_presents = 0;
 
         let top = sqrt(house) + 1;
@@ -38,7 +68,7 @@ fn part_2(puzzle_input: u32) -> u32 {
 
 
         if count_of_presents >= puzzle_input {
-            return house;
+            return Ok(house);
         }
 
         house += 1;
@@ -46,10 +76,11 @@ fn part_2(puzzle_input: u32) -> u32 {
 }
 
 
-fn part_1(puzzle_input: u32) -> u32 {
+fn part_1(puzzle_input: u32) -> Result<u32> {
     let mut house = 1;
     let packages_per_elf = 10;
     loop {
+        cancel::check()?;
         let mut count_of_presents = 0;
 
         let top = sqrt(house) + 1;
@@ -67,7 +98,7 @@ fn part_1(puzzle_input: u32) -> u32 {
 
 
         if count_of_presents >= puzzle_input {
-            return house;
+            return Ok(house);
         }
 
         house += 1;
@@ -79,12 +110,16 @@ fn sqrt(x: u32) -> u32 {
 }
 
 
-fn parse_input(input: Lines<BufReader<File>>) -> u32 {
-
-    for line in input {
-        let v = line.unwrap();
-        return v.parse::<u32>().unwrap();
+fn parse_input<R: BufRead>(mut input: Lines<R>) -> Result<u32> {
+    match input.next() {
+        Some(line) => {
+            let line = line?;
+            error::parse_number(1, &line, line.trim())
+        },
+        None => Err(PuzzleError::invalid_line(1, "", "expected the number of presents")),
     }
+}
 
-    return 0;
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (size.max(1) as i64 * 1000 + rng.range(0..=999)).to_string()
 }
@@ -1,9 +1,25 @@
 use std::cmp::Reverse;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
+
+use crate::error::{ self, PuzzleError, Result };
+use crate::generate::{ Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day21;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 21,
+    title: "RPG Simulator 20XX",
+    tags: &[Tag::Simulation, Tag::BruteForce],
+    examples: &[],
+    generator: Generator { size: "unused, there is one boss", default_size: 1, generate },
+    solver: &Solver::<Day21>::NEW,
+};
 
 #[derive(Debug)]
-struct Boss {
+pub struct Boss {
     hit_points: u32,
     damage: u32,
     armor: u32,
@@ -62,91 +78,96 @@ impl PlayerEquipment {
         for r in self.rings.iter() {
             ttl += r.cost;
         }
-        return ttl;
+        ttl
     }
 }
 
 #[derive(PartialEq, Debug)]
 
 enum Combatant {
-    PLAYER,
-    BOSS,
+    Player,
+    Boss,
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+impl Solution for Day21 {
+    type Input = Boss;
 
-    let boss = parse_input(input);
+    const PARAMS: &'static [Param] = &[
+        Param::integer("player-hp", "How many hit points the player starts with", 100),
+    ];
 
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Boss> {
+        parse_input(input)
+    }
 
+    fn part1(boss: &Boss, params: &Params) -> Result<Answer> {
+        let cost = part_1(boss, params.integer("player-hp")).ok_or_else(|| PuzzleError::unsolvable("Battle cannot be won!"))?;
+        Ok(cost.into())
+    }
 
-    let part1 = part_1(&boss);
-    println!("Part 1: Least amount of money we can spend and still win: {}", part1);
-
-
-    let part2 = part_2(&boss);
-    println!("Part 2: Most amount of money we can spend and still lose: {}", part2);
+    fn part2(boss: &Boss, params: &Params) -> Result<Answer> {
+        let cost = part_2(boss, params.integer("player-hp")).ok_or_else(|| PuzzleError::unsolvable("Battle cannot be lost!"))?;
+        Ok(cost.into())
+    }
 }
 
-
-fn part_1(boss: &Boss) -> u32 {
+fn part_1(boss: &Boss, player_hp: u32) -> Option<u32> {
     let (weapons_inventory, armor_inventory, ring_inventory) = item_shop_inventory();
     let mut combos = all_item_combos(weapons_inventory, armor_inventory, ring_inventory);
     combos.sort_by_cached_key(|x| x.cost());
 
     for combo in combos {
-        let winner = simulate_battle(100, &combo.to_stats(), &boss);
-        if winner == Combatant::PLAYER {
-            return combo.cost();
+        let winner = simulate_battle(player_hp, &combo.to_stats(), boss);
+        if winner == Combatant::Player {
+            return Some(combo.cost());
         }
     }
 
-    panic!("Battle cannot be won!");
+    None
 }
 
-fn part_2(boss: &Boss) -> u32 {
+fn part_2(boss: &Boss, player_hp: u32) -> Option<u32> {
     let (weapons_inventory, armor_inventory, ring_inventory) = item_shop_inventory();
     let mut combos = all_item_combos(weapons_inventory, armor_inventory, ring_inventory);
     combos.sort_by_cached_key(|x| Reverse(x.cost()));
 
     for combo in combos {
-        let winner = simulate_battle(100, &combo.to_stats(), &boss);
-        if winner == Combatant::BOSS {
-            return combo.cost();
+        let winner = simulate_battle(player_hp, &combo.to_stats(), boss);
+        if winner == Combatant::Boss {
+            return Some(combo.cost());
         }
     }
 
-    panic!("Battle cannot be won!");
+    None
 }
 
 fn simulate_battle(player_hp: u32, player_stats: &PlayerStats, boss: &Boss) -> Combatant {
     let mut player_hp = player_hp;
     let mut boss_hp = boss.hit_points;
 
-    let player_damage;
-    if player_stats.attack > boss.armor {
-        player_damage = player_stats.attack - boss.armor;
+    let player_damage = if player_stats.attack > boss.armor {
+        player_stats.attack - boss.armor
     } else {
-        player_damage = 1;
-    }
+        1
+    };
 
-    let boss_damage;
-    if boss.damage > player_stats.defense {
-        boss_damage = boss.damage - player_stats.defense;
+    let boss_damage = if boss.damage > player_stats.defense {
+        boss.damage - player_stats.defense
     } else {
-        boss_damage = 1;
-    }
+        1
+    };
 
     loop {
         if player_damage < boss_hp {
             boss_hp -= player_damage;
         } else {
-            return Combatant::PLAYER;
+            return Combatant::Player;
         }
 
         if boss_damage < player_hp {
             player_hp -= boss_damage;
         } else {
-            return Combatant::BOSS;
+            return Combatant::Boss;
         }
     }
 
@@ -154,20 +175,13 @@ fn simulate_battle(player_hp: u32, player_stats: &PlayerStats, boss: &Boss) -> C
 
 fn all_item_combos(weapons_inventory: Vec<Item>, armor_inventory: Vec<Item>, ring_inventory: Vec<Item>) -> Vec<PlayerEquipment> {
     // all possibilities for choosing either zero, one, or two rings.
-    let mut ring_choices: Vec<Vec<Item>> = Vec::new();
-
-    ring_choices.push(vec!());
-    for i in 0..ring_inventory.len() {
-        let ring1 = ring_inventory.get(i).unwrap();
-        let ring1 = (*ring1).clone();
+    let mut ring_choices: Vec<Vec<Item>> = vec![vec!()];
 
+    for (i, ring1) in ring_inventory.iter().enumerate() {
         ring_choices.push(vec!(ring1.clone()));
 
-        for j in (i+1)..ring_inventory.len() {
-            let ring2 = ring_inventory.get(j).unwrap();
-            let ring2 = (*ring2).clone();
-
-            ring_choices.push(vec!(ring1.clone(), ring2));
+        for ring2 in &ring_inventory[(i+1)..] {
+            ring_choices.push(vec!(ring1.clone(), ring2.clone()));
         }
     }
 
@@ -193,28 +207,39 @@ fn all_item_combos(weapons_inventory: Vec<Item>, armor_inventory: Vec<Item>, rin
         }
     }
 
-    return combos;
+    combos
 }
 
-fn parse_input(input: Lines<BufReader<File>>) -> Boss {
-    let mut hit_points = 0;
-    let mut damage = 0;
-    let mut armor = 0;
+fn parse_input<R: BufRead>(input: Lines<R>) -> Result<Boss> {
+    let mut hit_points = None;
+    let mut damage = None;
+    let mut armor = None;
 
-    for line in input {
-        let v = line.unwrap();
+    let mut line_count = 0;
+    for (i, line) in input.enumerate() {
+        line_count = i+1;
+        let v = line?;
         let split: Vec<&str> = v.split(": ").collect();
+        if split.len() != 2 {
+            return Err(PuzzleError::invalid_line(i+1, &v, "expected a stat like 'Hit Points: 100'"));
+        }
         let key = split[0];
-        let val = split[1].parse::<u32>().unwrap();
+        let val = error::parse_number::<u32>(i+1, &v, split[1])?;
 
         match key {
-            "Hit Points" => hit_points = val,
-            "Damage" => damage = val,
-            "Armor" => armor = val,
-            _ => panic!("Unexpected input: {}", key),
+            "Hit Points" => hit_points = Some(val),
+            "Damage" => damage = Some(val),
+            "Armor" => armor = Some(val),
+            _ => return Err(PuzzleError::invalid_token(i+1, &v, key, format!("unexpected stat '{}'", key))),
         }    
     }
-    Boss{hit_points, damage, armor}
+
+    let missing = |stat| PuzzleError::invalid_line(line_count+1, "", format!("expected the boss's {} stat", stat));
+    Ok(Boss{
+        hit_points: hit_points.ok_or_else(|| missing("Hit Points"))?,
+        damage: damage.ok_or_else(|| missing("Damage"))?,
+        armor: armor.ok_or_else(|| missing("Armor"))?,
+    })
 }
 
 fn item_shop_inventory() -> (Vec<Item>, Vec<Item>, Vec<Item>) {
@@ -301,13 +326,36 @@ fn item_shop_inventory() -> (Vec<Item>, Vec<Item>, Vec<Item>) {
             damage: 0,
             armor: 3},
     );
-    return (weapons, armor, rings);
+    (weapons, armor, rings)
 }
 
+/// A boss like the real ones, which can be beaten with some equipment but not all.
+fn generate(rng: &mut Rng, _size: usize) -> String {
+    format!("Hit Points: {}\nDamage: {}\nArmor: {}", rng.range(90..=110), rng.range(6..=9), rng.range(1..=3))
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The line and message of a parse error.
+    fn error_at(result: Result<Boss>) -> (usize, String) {
+        match result {
+            Err(PuzzleError::Parse { line, message, .. }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Day21::parse("Hit Points: 104\nDamage: 8\nArmor: 1".as_bytes().lines()).is_ok());
+        assert_eq!((3, String::from("expected the boss's Damage stat")), error_at(Day21::parse("Hit Points: 104\nArmor: 1".as_bytes().lines())));
+        assert_eq!((3, String::from("expected the boss's Armor stat")), error_at(Day21::parse("Hit Points: 104\nDamage: 8".as_bytes().lines())));
+        assert_eq!((1, String::from("expected the boss's Hit Points stat")), error_at(Day21::parse("".as_bytes().lines())));
+        assert_eq!((2, String::from("unexpected stat 'Mana'")), error_at(Day21::parse("Hit Points: 104\nMana: 8".as_bytes().lines())));
+    }
+
     #[test]
     fn test_simulate_battle_player_wins() {
         let player_stats = PlayerStats{attack: 5, defense: 5};
@@ -316,7 +364,7 @@ mod tests {
         let boss = Boss{damage: 7, armor: 2, hit_points: 12};
 
         let result = simulate_battle(player_hp, &player_stats, &boss);
-        assert_eq!(Combatant::PLAYER, result);
+        assert_eq!(Combatant::Player, result);
     }
 
     #[test]
@@ -327,7 +375,7 @@ mod tests {
         let boss = Boss{damage: 7, armor: 2, hit_points: 13};
 
         let result = simulate_battle(player_hp, &player_stats, &boss);
-        assert_eq!(Combatant::BOSS, result);
+        assert_eq!(Combatant::Boss, result);
     }
 
 }
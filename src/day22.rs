@@ -1,8 +1,34 @@
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::hash::{ Hash, Hasher };
+use std::io::{ BufRead, Lines };
 use self::PlayerAction::*;
 
+use crate::error::{ self, PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::search::{ self, State };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day22;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 22,
+    title: "Wizard Simulator 20XX",
+    tags: &[Tag::Simulation, Tag::Search],
+    examples: EXAMPLES,
+    generator: Generator { size: "unused, there is one boss", default_size: 1, generate },
+    solver: &Solver::<Day22>::NEW,
+};
+
+/// Examples from the problem statement, where the player has 10 hit points and 250 mana.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "Hit Points: 13\nDamage: 8", expected: "226", params: EXAMPLE_PLAYER },
+    Example { part: 1, input: "Hit Points: 14\nDamage: 8", expected: "641", params: EXAMPLE_PLAYER },
+];
+
+const EXAMPLE_PLAYER: &[(&str, &str)] = &[("player-hp", "10"), ("player-mana", "250")];
+
 const MIN_SPELL_COST: u32 = 53;
 const MAGIC_MISSILE_COST: u32 = 53;
 const DRAIN_COST: u32 = 73;
@@ -25,8 +51,8 @@ impl PlayerAction {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Boss {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Boss {
     hit_points: u32,
     damage: u32,
 }
@@ -46,7 +72,28 @@ struct Player {
 }
 
 
+// The mana spent is left out of comparisons, as the search already keeps track
+// of it as the cost of reaching a state
+impl PartialEq for Player {
+    fn eq(&self, other: &Self) -> bool {
+        self.state() == other.state()
+    }
+}
+
+impl Eq for Player {}
+
+impl Hash for Player {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.state().hash(state);
+    }
+}
+
 impl Player {
+    /// Everything about the player that matters for the rest of the fight.
+    fn state(&self) -> (u32, u32, u32, u32, u32, u32) {
+        (self.mana, self.hit_points, self.armor, self.shield_effect, self.poison_effect, self.recharge_effect)
+    }
+
     fn new(mana: u32, hit_points: u32) -> Self {
         Player{
             mana, 
@@ -121,11 +168,11 @@ impl Player {
 
 #[derive(PartialEq, Debug)]
 enum Combatant {
-    PLAYER,
-    BOSS,
+    Player,
+    Boss,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Game {
     player: Player,
     boss: Boss,
@@ -135,12 +182,12 @@ struct Game {
 impl Game {
     fn winner(&self) -> Option<Combatant> {
         if self.player.hit_points == 0 {
-            return Option::Some(Combatant::BOSS);
+            return Option::Some(Combatant::Boss);
         }
         if self.boss.hit_points == 0 {
-            return Option::Some(Combatant::PLAYER);
+            return Option::Some(Combatant::Player);
         }
-        return Option::None;
+        Option::None
     }
 
     fn apply_effects(&mut self) {
@@ -151,7 +198,7 @@ impl Game {
             }
         }
         if self.player.poison_effect > 0 {
-            self.boss.hit_points -= 3;
+            self.boss.hit_points = self.boss.hit_points.saturating_sub(3);
             self.player.poison_effect -= 1;
         }
         if self.player.recharge_effect > 0 {
@@ -163,8 +210,8 @@ impl Game {
     fn player_turn(&mut self, action: PlayerAction) -> Option<Combatant> {
         if self.hard_mode {
             self.player.hit_points -= 1;
-            if self.player.hit_points <= 0 {
-                return Option::Some(Combatant::BOSS);
+            if self.player.hit_points == 0 {
+                return Option::Some(Combatant::Boss);
             }
         }
 
@@ -173,7 +220,7 @@ impl Game {
         if winner.is_some() {
             return winner;
         } else if self.player.mana < MIN_SPELL_COST {
-            return Option::Some(Combatant::BOSS);
+            return Option::Some(Combatant::Boss);
         }
 
         let damage = self.player.take_turn(action);
@@ -206,137 +253,113 @@ impl Game {
     }
 }
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-
-    let boss = parse_input(input);
-
-    let part1 = part_1(&boss);
-    println!("Part 1: Least amount of mana we can spend and still win: {}", part1);
-
-
-    let part2 = part_2(&boss);
-    println!("Part 2: Least amount of mana we can spend and still win: {}", part2);
-}
-
-
-fn part_1(boss: &Boss) -> u32 {
-    let player = Player::new(500, 50);
-    let game = Game{player, boss: boss.clone(), hard_mode: false};
-    let mut lowest_mana_cost_of_victory = u32::MAX;
-
-    let mut queue: VecDeque<Game> = VecDeque::new();
-    queue.push_back(game);
-
-
-    while !queue.is_empty() {
-        let current_state = queue.pop_front().unwrap();
+/// A battle at the start of one of the player's turns. Each step is a spell and
+/// the boss's turn that follows it, and costs the mana the spell took.
+impl State for Game {
+    type Cost = u32;
 
+    fn neighbors(&self) -> Vec<(Game, u32)> {
+        let mut next = Vec::new();
         for action in PlayerAction::iterator() {
-            if !current_state.player.can_perform_action(action) {
+            if !self.player.can_perform_action(action) {
                 continue;
             }
 
-            let mut g = current_state.clone();
-            let winner = g.player_turn(action);
-
-            if g.player.mana_spent >= lowest_mana_cost_of_victory {
-                continue;
-            }
-
-            if winner.is_some() {
-                let winner = winner.unwrap();
-                if winner == Combatant::PLAYER && 
-                    g.player.mana_spent < lowest_mana_cost_of_victory {
-                        lowest_mana_cost_of_victory = g.player.mana_spent;
-                    }
-                continue;
+            let mut game = self.clone();
+            let winner = match game.player_turn(action) {
+                None => game.boss_turn(),
+                winner => winner,
+            };
+            if winner != Some(Combatant::Boss) {
+                let mana = game.player.mana_spent - self.player.mana_spent;
+                next.push((game, mana));
             }
-            let winner = g.boss_turn();
-
-            if winner.is_some() {
-                let winner = winner.unwrap();
-                if winner == Combatant::PLAYER && 
-                    g.player.mana_spent < lowest_mana_cost_of_victory {
-                        lowest_mana_cost_of_victory = g.player.mana_spent;
-                    }
-                continue;
-            }
-
-            queue.push_back(g);
         }
+        next
     }
-    
-    return lowest_mana_cost_of_victory;
-}
 
-fn part_2(boss: &Boss) -> u32 {
-    let player = Player::new(500, 50);
-    let game = Game{player, boss: boss.clone(), hard_mode: true};
-    let mut lowest_mana_cost_of_victory = u32::MAX;
+    fn is_goal(&self) -> bool {
+        self.winner() == Some(Combatant::Player)
+    }
 
-    let mut queue: VecDeque<Game> = VecDeque::new();
-    queue.push_back(game);
+    fn heuristic(&self) -> u32 {
+        // Poison does the most damage for its mana, 18 over its six turns, so the
+        // rest of the boss's hit points cannot cost less than poisoning them all
+        let remaining = self.boss.hit_points.saturating_sub(3 * self.player.poison_effect);
+        remaining * POISON_COST / 18
+    }
+}
 
+impl Solution for Day22 {
+    type Input = Boss;
 
-    while !queue.is_empty() {
-        let current_state = queue.pop_front().unwrap();
+    const PARAMS: &'static [Param] = &[
+        Param::positive("player-hp", "How many hit points the player starts with", 50),
+        Param::integer("player-mana", "How much mana the player starts with", 500),
+    ];
 
-        for action in PlayerAction::iterator() {
-            if !current_state.player.can_perform_action(action) {
-                continue;
-            }
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Boss> {
+        parse_input(input)
+    }
 
-            let mut g = current_state.clone();
-            let winner = g.player_turn(action);
+    fn part1(boss: &Boss, params: &Params) -> Result<Answer> {
+        least_mana_to_win(boss, player(params), false)?
+            .map(Answer::from)
+            .ok_or_else(|| PuzzleError::unsolvable("the boss cannot be beaten"))
+    }
 
-            if g.player.mana_spent >= lowest_mana_cost_of_victory {
-                continue;
-            }
+    fn part2(boss: &Boss, params: &Params) -> Result<Answer> {
+        least_mana_to_win(boss, player(params), true)?
+            .map(Answer::from)
+            .ok_or_else(|| PuzzleError::unsolvable("the boss cannot be beaten in hard mode"))
+    }
+}
 
-            if winner.is_some() {
-                let winner = winner.unwrap();
-                if winner == Combatant::PLAYER && 
-                    g.player.mana_spent < lowest_mana_cost_of_victory {
-                        lowest_mana_cost_of_victory = g.player.mana_spent;
-                    }
-                continue;
-            }
-            let winner = g.boss_turn();
-
-            if winner.is_some() {
-                let winner = winner.unwrap();
-                if winner == Combatant::PLAYER && 
-                    g.player.mana_spent < lowest_mana_cost_of_victory {
-                        lowest_mana_cost_of_victory = g.player.mana_spent;
-                    }
-                continue;
-            }
+/// The player at the start of the battle.
+fn player(params: &Params) -> Player {
+    Player::new(params.integer("player-mana"), params.integer("player-hp"))
+}
 
-            queue.push_back(g);
-        }
-    }
-    
-    return lowest_mana_cost_of_victory;
+/// The least mana the player can spend and still win, or `None` if the boss
+/// always wins.
+fn least_mana_to_win(boss: &Boss, player: Player, hard_mode: bool) -> Result<Option<u32>> {
+    let game = Game { player, boss: boss.clone(), hard_mode };
+    Ok(search::astar(game)?.map(|path| path.cost))
 }
 
 
-fn parse_input(input: Lines<BufReader<File>>) -> Boss {
-    let mut hit_points = 0;
-    let mut damage = 0;
+fn parse_input<R: BufRead>(input: Lines<R>) -> Result<Boss> {
+    let mut hit_points = None;
+    let mut damage = None;
 
-    for line in input {
-        let v = line.unwrap();
+    let mut line_count = 0;
+    for (i, line) in input.enumerate() {
+        line_count = i+1;
+        let v = line?;
         let split: Vec<&str> = v.split(": ").collect();
+        if split.len() != 2 {
+            return Err(PuzzleError::invalid_line(i+1, &v, "expected a stat like 'Hit Points: 100'"));
+        }
         let key = split[0];
-        let val = split[1].parse::<u32>().unwrap();
+        let val = error::parse_number::<u32>(i+1, &v, split[1])?;
 
         match key {
-            "Hit Points" => hit_points = val,
-            "Damage" => damage = val,
-            _ => panic!("Unexpected input: {}", key),
+            "Hit Points" => hit_points = Some(val),
+            "Damage" => damage = Some(val),
+            _ => return Err(PuzzleError::invalid_token(i+1, &v, key, format!("unexpected stat '{}'", key))),
         }    
     }
-    Boss{hit_points, damage}
+
+    let missing = |stat| PuzzleError::invalid_line(line_count+1, "", format!("expected the boss's {} stat", stat));
+    Ok(Boss{
+        hit_points: hit_points.ok_or_else(|| missing("Hit Points"))?,
+        damage: damage.ok_or_else(|| missing("Damage"))?,
+    })
+}
+
+/// A boss like the real ones.
+fn generate(rng: &mut Rng, _size: usize) -> String {
+    format!("Hit Points: {}\nDamage: {}", rng.range(51..=71), rng.range(8..=10))
 }
 
 
@@ -344,6 +367,22 @@ fn parse_input(input: Lines<BufReader<File>>) -> Boss {
 mod tests {
     use super::*;
 
+    /// The line and message of a parse error.
+    fn error_at(result: Result<Boss>) -> (usize, String) {
+        match result {
+            Err(PuzzleError::Parse { line, message, .. }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Day22::parse("Hit Points: 58\nDamage: 9".as_bytes().lines()).is_ok());
+        assert_eq!((2, String::from("expected the boss's Hit Points stat")), error_at(Day22::parse("Damage: 9".as_bytes().lines())));
+        assert_eq!((2, String::from("expected the boss's Damage stat")), error_at(Day22::parse("Hit Points: 58\n".as_bytes().lines())));
+        assert_eq!((2, String::from("expected a stat like 'Hit Points: 100'")), error_at(Day22::parse("Hit Points: 58\nDamage nine".as_bytes().lines())));
+    }
+
     #[test]
     fn test_cast_magic_missile() {
         let player = Player::new(250, 10);
@@ -378,6 +417,17 @@ mod tests {
         assert_eq!(0, game.player.recharge_effect);
     }
 
+    #[test]
+    fn test_no_hit_points_is_rejected() {
+        let mut params = Params::new(Day22::PARAMS);
+        assert!(params.set("player-hp", "0").is_err());
+
+        params.set("player-hp", "1").unwrap();
+        let mut game = Game{player: player(&params), boss: Boss{hit_points: 13, damage: 8}, hard_mode: true};
+        assert_eq!(Some(Combatant::Boss), game.player_turn(PlayerAction::MagicMissile));
+        assert_eq!(0, game.player.hit_points);
+    }
+
     #[test]
     fn test_example_1() {
         let player = Player::new(250, 10);
@@ -416,7 +466,7 @@ mod tests {
 
         let result = game.boss_turn();
         assert!(result.is_some());
-        assert_eq!(Combatant::PLAYER, result.unwrap());
+        assert_eq!(Combatant::Player, result.unwrap());
         assert_eq!(0, game.boss.hit_points);
         assert_eq!(2, game.player.hit_points);
         assert_eq!(24, game.player.mana);
@@ -455,7 +505,7 @@ mod tests {
 
         let result = game.player_turn(PlayerAction::MagicMissile);
         assert!(result.is_some());
-        assert_eq!(Combatant::BOSS, result.unwrap());
+        assert_eq!(Combatant::Boss, result.unwrap());
         assert_eq!(13, game.boss.hit_points);
         assert_eq!(0, game.player.hit_points);
         assert_eq!((500-RECHARGE_COST+101), game.player.mana);
@@ -464,4 +514,13 @@ mod tests {
         assert_eq!(0, game.player.poison_effect);
         assert_eq!(4, game.player.recharge_effect);
     }
+
+    #[test]
+    fn test_mana_spent_is_not_part_of_the_state() {
+        let mut player = Player::new(250, 10);
+        let mut other = player.clone();
+        player.mana_spent = 53;
+        other.mana_spent = 73;
+        assert_eq!(player, other);
+    }
 }
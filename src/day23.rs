@@ -1,14 +1,31 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::io::{ BufRead, Lines };
+
+use crate::cancel;
+use crate::error::{ self, PuzzleError, Result };
+use crate::generate::{ Generator, Rng };
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day23;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 23,
+    title: "Opening the Turing Lock",
+    tags: &[Tag::Simulation],
+    examples: &[],
+    generator: Generator { size: "number of instructions that compute the starting values", default_size: 40, generate },
+    solver: &Solver::<Day23>::NEW,
+};
 
 #[derive(Debug)]
-enum Register {
+pub enum Register {
     A,
     B,
 }
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Half(Register),
     Triple(Register),
     Increment(Register),
@@ -70,8 +87,8 @@ impl Computer {
 
     fn jie(&mut self, r: &Register, offset: i32) {
         let condition = match r {
-            Register::A => self.a % 2 == 0,
-            Register::B => self.b % 2 == 0,
+            Register::A => self.a.is_multiple_of(2),
+            Register::B => self.b.is_multiple_of(2),
         };
         if condition {
             self.instr += offset;
@@ -101,24 +118,28 @@ const INSTR_JMP: &str = "jmp";
 const INSTR_JMP_EVEN: &str = "jie";
 const INSTR_JMP_ODD: &str = "jio";
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+impl Solution for Day23 {
+    type Input = Vec<Instruction>;
 
-    let instructions = parse_input(input);
-
-
-    let part1 = part_1(&instructions);
-    println!("Part 1: Value of register b: {}", part1);
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<Instruction>> {
+        parse_input(input)
+    }
 
+    fn part1(program: &Vec<Instruction>, _params: &Params) -> Result<Answer> {
+        Ok(part_1(program)?.into())
+    }
 
-    let part2 = part_2(&instructions);
-    println!("Part 2: Value of register b: {}", part2);
+    fn part2(program: &Vec<Instruction>, _params: &Params) -> Result<Answer> {
+        Ok(part_2(program)?.into())
+    }
 }
 
-
-fn part_1(program: &Vec<Instruction>) -> u32 {
+fn part_1(program: &[Instruction]) -> Result<u32> {
     let mut cpu = Computer::new();
 
     while 0 <= cpu.instr && cpu.instr < program.len() as i32 {
+        // A program can loop forever
+        cancel::check()?;
         let i = cpu.instr as usize;
         let instruction = program.get(i).unwrap();
         cpu.execute(instruction);
@@ -126,14 +147,16 @@ fn part_1(program: &Vec<Instruction>) -> u32 {
         // println!("Executed instr {} {:?}. {:?}", i, instruction, cpu);
     }
 
-    cpu.b
+    Ok(cpu.b)
 }
 
-fn part_2(program: &Vec<Instruction>) -> u32 {
+fn part_2(program: &[Instruction]) -> Result<u32> {
     let mut cpu = Computer::new();
     cpu.a = 1;
 
     while 0 <= cpu.instr && cpu.instr < program.len() as i32 {
+        // A program can loop forever
+        cancel::check()?;
         let i = cpu.instr as usize;
         let instruction = program.get(i).unwrap();
         cpu.execute(instruction);
@@ -141,62 +164,121 @@ fn part_2(program: &Vec<Instruction>) -> u32 {
         // println!("Executed instr {} {:?}. {:?}", i, instruction, cpu);
     }
 
-    cpu.b
+    Ok(cpu.b)
 }
 
 
-fn parse_input<'b>(input: Lines<BufReader<File>>) -> Vec<Instruction> {
+fn parse_input<R: BufRead>(input: Lines<R>) -> Result<Vec<Instruction>> {
     let mut program: Vec<Instruction> = Vec::new();
 
-    for line in input {
-        let v = line.unwrap().replace(",", "");
-        let split: Vec<&str> = v.split(" ").collect();
+    for (i, line) in input.enumerate() {
+        let line = line?;
+        let line_number = i+1;
+        let split: Vec<&str> = line.split(' ').map(|s| s.trim_end_matches(',')).collect();
 
         let instruction_name = split[0];
+        let (expected_operands, usage) = match instruction_name {
+            INSTR_HALF | INSTR_TRIPLE | INSTR_INCR => (1, "expected a register, like 'inc a'"),
+            INSTR_JMP => (1, "expected an offset, like 'jmp +2'"),
+            INSTR_JMP_EVEN | INSTR_JMP_ODD => (2, "expected a register and an offset, like 'jie a, +2'"),
+            _ => return Err(PuzzleError::invalid_token(line_number, &line, instruction_name,
+                format!("unknown instruction '{}'", instruction_name))),
+        };
+        if split.len() != expected_operands + 1 {
+            return Err(PuzzleError::invalid_line(line_number, &line, usage));
+        }
 
-        let target_register = match split[1] {
-            "a" => Register::A,
-            _ => Register::B, // if it's not "a" or "b" then it's a jump instruction
+        let instruction = match instruction_name {
+            INSTR_HALF => Instruction::Half(parse_register(line_number, &line, split[1])?),
+            INSTR_TRIPLE => Instruction::Triple(parse_register(line_number, &line, split[1])?),
+            INSTR_INCR => Instruction::Increment(parse_register(line_number, &line, split[1])?),
+            INSTR_JMP => Instruction::Jump(error::parse_number(line_number, &line, split[1])?),
+            INSTR_JMP_EVEN => Instruction::JumpIfEven(
+                parse_register(line_number, &line, split[1])?,
+                error::parse_number(line_number, &line, split[2])?),
+            _ => Instruction::JumpIfOne(
+                parse_register(line_number, &line, split[1])?,
+                error::parse_number(line_number, &line, split[2])?),
         };
+        program.push(instruction);
+    }
+    Ok(program)
+}
 
-        match instruction_name {
-            INSTR_HALF => program.push(Instruction::Half(target_register)),
-            INSTR_TRIPLE => program.push(Instruction::Triple(target_register)),
-            INSTR_INCR => program.push(Instruction::Increment(target_register)),
-            INSTR_JMP => {
-                let value = split[1].parse::<i32>().unwrap();
-                program.push(Instruction::Jump(value));
-            },
-            INSTR_JMP_EVEN => {
-                let value = split[2].parse::<i32>().unwrap();
-                program.push(Instruction::JumpIfEven(target_register, value));
-            },
-            INSTR_JMP_ODD => {
-                let value = split[2].parse::<i32>().unwrap();
-                program.push(Instruction::JumpIfOne(target_register, value));
-            },
-            _ => panic!("Unexpected instruction name"),
+fn parse_register(line_number: usize, line: &str, token: &str) -> Result<Register> {
+    match token {
+        "a" => Ok(Register::A),
+        "b" => Ok(Register::B),
+        _ => Err(PuzzleError::invalid_token(line_number, line, token, "expected register 'a' or 'b'")),
+    }
+}
+
+/// A program shaped like the real one: it computes a starting value for `a`, a
+/// different one when `a` starts at 1, then counts the steps of that value's
+/// Collatz sequence in `b`. The values are kept small enough that the sequence
+/// never overflows a register.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    loop {
+        let (part1, a1) = starting_value(rng, (size / 2).max(1), 0);
+        let (part2, a2) = starting_value(rng, (size - size / 2).max(1), 1);
+        if !collatz_fits(a1) || !collatz_fits(a2) {
+            continue;
         }
+
+        let mut lines = vec![format!("jio a, {:+}", part1.len() + 2)];
+        lines.extend(part1);
+        lines.push(format!("jmp {:+}", part2.len() + 1));
+        lines.extend(part2);
+        lines.extend(["jio a, +8", "inc b", "jie a, +4", "tpl a", "inc a", "jmp +2", "hlf a", "jmp -7"].map(String::from));
+        return lines.join("\n");
     }
-    return program;
+}
+
+/// `len` instructions that turn `a` from `start` into some larger value, along with that value.
+fn starting_value(rng: &mut Rng, len: usize, start: u64) -> (Vec<String>, u64) {
+    let mut a = start;
+    let mut instructions = Vec::with_capacity(len);
+    for _ in 0..len {
+        if a > 0 && a * 3 <= 100_000 && rng.chance(1, 2) {
+            a *= 3;
+            instructions.push(String::from("tpl a"));
+        } else {
+            a += 1;
+            instructions.push(String::from("inc a"));
+        }
+    }
+    (instructions, a)
+}
+
+fn collatz_fits(mut n: u64) -> bool {
+    while n > 1 {
+        n = if n.is_multiple_of(2) { n / 2 } else { 3 * n + 1 };
+        if n > u32::MAX as u64 {
+            return false;
+        }
+    }
+    true
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     #[test]
     fn test_example_program() {
-        let mut program: Vec<Instruction> = Vec::new();
-        program.push(Instruction::Increment(Register::A));
-        program.push(Instruction::JumpIfOne(Register::A, 2));
-        program.push(Instruction::Triple(Register::A));
-        program.push(Instruction::Increment(Register::A));
+        let program: Vec<Instruction> = vec![
+            Instruction::Increment(Register::A),
+            Instruction::JumpIfOne(Register::A, 2),
+            Instruction::Triple(Register::A),
+            Instruction::Increment(Register::A),
+        ];
         
         let mut cpu = Computer::new();
 
-        cpu.execute(program.get(0).unwrap());
+        cpu.execute(program.first().unwrap());
         assert_eq!(1, cpu.a);
         assert_eq!(0, cpu.b);
         assert_eq!(1, cpu.instr);
@@ -212,4 +294,11 @@ mod tests {
         assert_eq!(4, cpu.instr);
         
     }
+
+    #[test]
+    fn test_infinite_loop_times_out() {
+        let program = vec![Instruction::Jump(0)];
+        let result = cancel::with_timeout(Some(Duration::from_millis(10)), || part_1(&program));
+        assert!(matches!(result, Err(PuzzleError::TimedOut(_))));
+    }
 }
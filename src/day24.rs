@@ -1,21 +1,55 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
+use std::io::{ BufRead, Lines };
+
+use crate::error::{ self, PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::input::Normalize;
+use crate::params::Params;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day24;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 24,
+    title: "It Hangs in the Balance",
+    tags: &[Tag::Combinatorics, Tag::Search],
+    examples: EXAMPLES,
+    generator: Generator { size: "number of packages, at least 24", default_size: 28, generate },
+    solver: &Solver::<Day24>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: EXAMPLE_WEIGHTS, expected: "99", params: &[] },
+    Example { part: 2, input: EXAMPLE_WEIGHTS, expected: "44", params: &[] },
+];
+
+const EXAMPLE_WEIGHTS: &str = "1\n2\n3\n4\n5\n7\n8\n9\n10\n11";
+
+impl Solution for Day24 {
+    type Input = Vec<u64>;
+
+    // One number per line, so blank lines carry no meaning
+    const NORMALIZE: Normalize = Normalize { drop_blank_lines: true, ..Normalize::DEFAULT };
+
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Vec<u64>> {
+        let present_weights = parse_input(input)?;
+        validated_present_weights(&present_weights)
+    }
 
-    let present_weights = parse_input(input);
-    let present_weights = validated_present_weights(&present_weights);
-    
-    let part1 = part_1(present_weights.clone());
-    println!("Part 1: {}", part1);
+    fn part1(present_weights: &Vec<u64>, _params: &Params) -> Result<Answer> {
+        Ok(part_1(present_weights.clone())?.into())
+    }
 
-    let part2 = part_2(present_weights);
-    println!("Part 2: {}", part2);
+    fn part2(present_weights: &Vec<u64>, _params: &Params) -> Result<Answer> {
+        Ok(part_2(present_weights.clone())?.into())
+    }
 }
 
-fn part_1(present_weights: Vec<u64>) -> u64 {
+fn part_1(present_weights: Vec<u64>) -> Result<u64> {
     // Since we know exactly how many compartments there are, we know exactly how much weight should end up in each one
-    let target = target_weight_per_compartment(3, &present_weights);
+    let target = target_weight_per_compartment(3, &present_weights)?;
     
     // Look for combinations of presents that add up to the target weight.
     // Generating a full list of all the possible combinations that add up to that weight will take a long time,
@@ -33,64 +67,62 @@ fn part_1(present_weights: Vec<u64>) -> u64 {
         // it still needs to be possible to divide the remaining presents into equal partitions so that the sleigh is balanced.
         // Even if a combination of presents has weight==target, it is not really a valid combination unless
         // the remaining presents can be equally split up into two combos whose total weight is also `target`.
-        let valid_combos: Vec<Vec<u64>> = combos.iter()
-            .map(|x| x.clone())
+        let valid_combos: Vec<Vec<u64>> = combos.into_iter()
             .filter(|c| is_valid_three_way_split(target, c, &present_weights))
             .collect();
 
         // Because we're taking a greedy approach, once we've found any valid combinations at all, we know that they are
         // of the smallest possible size (ie. fewest number of presents). If there are multiple such combinations,
         // the tiebreaker is the "quantum entanglement".
-        if valid_combos.len() > 0 {
+        if !valid_combos.is_empty() {
             let entanglements: Vec<u64> = valid_combos.iter().map(|x| x.iter().product()).collect();
             match entanglements.iter().min() {
-                Some(&x) => return x,
+                Some(&x) => return Ok(x),
                 None => panic!("Entanglements vec is empty?!"),
             }
         }
     }
 
-    panic!("Failed to find valid balancing arrangement! Do you need to search with a larger i value?");
+    Err(PuzzleError::unsolvable("Failed to find valid balancing arrangement! Do you need to search with a larger i value?"))
 }
 
-fn part_2(present_weights: Vec<u64>) -> u64 {
-    let target = target_weight_per_compartment(4, &present_weights);
+fn part_2(present_weights: Vec<u64>) -> Result<u64> {
+    let target = target_weight_per_compartment(4, &present_weights)?;
 
     for i in 1..present_weights.len() {
         let combos = get_combinations_summing_to_n(target, present_weights.clone(), i);
 
-        let valid_combos: Vec<Vec<u64>> = combos.iter()
-            .map(|x| x.clone())
+        let valid_combos: Vec<Vec<u64>> = combos.into_iter()
             .filter(|c| is_valid_four_way_split(target, c, &present_weights))
             .collect();
 
-        if valid_combos.len() > 0 {
+        if !valid_combos.is_empty() {
             let entanglements: Vec<u64> = valid_combos.iter().map(|x| x.iter().product()).collect();
             match entanglements.iter().min() {
-                Some(&x) => return x,
+                Some(&x) => return Ok(x),
                 None => panic!("Entanglements vec is empty?!"),
             }
         }
     }
 
-    panic!("Failed to find valid balancing arrangement! Do you need to search with a larger i value?");
+    Err(PuzzleError::unsolvable("Failed to find valid balancing arrangement! Do you need to search with a larger i value?"))
 }
 
-fn validated_present_weights(present_weights: &Vec<u64>) -> Vec<u64> {
-    let mut present_weights = present_weights.clone();
+fn validated_present_weights(present_weights: &[u64]) -> Result<Vec<u64>> {
+    let mut present_weights = present_weights.to_vec();
     present_weights.sort();
     present_weights.reverse();
     let len = present_weights.len();
     present_weights.dedup();
     if present_weights.len() != len {
-        panic!("Input data doesn't conform to the critical assumption that there no two presents have the exact same weight");
+        return Err(PuzzleError::unsolvable("Input data doesn't conform to the critical assumption that there no two presents have the exact same weight"));
     }
 
-    return present_weights;
+    Ok(present_weights)
 }
 
 fn get_combinations_summing_to_n(target_sum: u64, nums: Vec<u64>, max_size: usize) -> Vec<Vec<u64>> {
-    return combinations_sum_n(&[], target_sum, &nums, max_size);
+    combinations_sum_n(&[], target_sum, &nums, max_size)
 }
 
 fn combinations_sum_n(prev: &[u64], n: u64, nums: &[u64], max_size: usize) -> Vec<Vec<u64>> {
@@ -118,10 +150,10 @@ fn combinations_sum_n(prev: &[u64], n: u64, nums: &[u64], max_size: usize) -> Ve
             retval.append(&mut recurse);
         }
     }
-    return retval;
+    retval
 }
 
-fn is_valid_four_way_split(target_weight: u64, combo: &Vec<u64>, present_weights: &Vec<u64>) -> bool {
+fn is_valid_four_way_split(target_weight: u64, combo: &[u64], present_weights: &[u64]) -> bool {
     let filtered_weights: Vec<u64> = present_weights.iter().copied().filter(|&x| !combo.contains(&x)).collect();
     for i in 1..10 {
         let combos = get_combinations_summing_to_n(target_weight, filtered_weights.clone(), i);
@@ -135,9 +167,9 @@ fn is_valid_four_way_split(target_weight: u64, combo: &Vec<u64>, present_weights
     false
 }
 
-fn is_valid_three_way_split(target_weight: u64, combo: &Vec<u64>, present_weights: &Vec<u64>) -> bool {
+fn is_valid_three_way_split(target_weight: u64, combo: &[u64], present_weights: &[u64]) -> bool {
     let filtered_weights: Vec<u64> = present_weights.iter().copied().filter(|&x| !combo.contains(&x)).collect();
-    return can_make_total(&[], target_weight, &filtered_weights);
+    can_make_total(&[], target_weight, &filtered_weights)
 }
 
 fn can_make_total(prev: &[u64], n: u64, nums: &[u64]) -> bool {
@@ -159,22 +191,56 @@ fn can_make_total(prev: &[u64], n: u64, nums: &[u64]) -> bool {
     false
 }
 
-fn target_weight_per_compartment(num_compartments: u64, present_weights: &Vec<u64>) -> u64 {
+fn target_weight_per_compartment(num_compartments: u64, present_weights: &[u64]) -> Result<u64> {
     let ttl: u64 = present_weights.iter().sum();
-    assert!(ttl % num_compartments == 0);
-    ttl / num_compartments
+    if !ttl.is_multiple_of(num_compartments) {
+        return Err(PuzzleError::unsolvable(format!("a total weight of {} cannot be split into {} equal groups", ttl, num_compartments)));
+    }
+    Ok(ttl / num_compartments)
 }
 
-fn parse_input(input: Lines<BufReader<File>>) -> Vec<u64> {
+fn parse_input<R: BufRead>(input: Lines<R>) -> Result<Vec<u64>> {
     let mut present_weights: Vec<u64> = Vec::new();
 
-    for line in input {
-        let line = line.unwrap();
-        present_weights.push(line.parse::<u64>().unwrap());
+    for line in Day24::NORMALIZE.lines(input) {
+        let (line_no, line) = line?;
+        present_weights.push(error::parse_number(line_no, &line, line.trim())?);
     }
     present_weights.sort();
 
-    return present_weights;
+    Ok(present_weights)
+}
+
+/// Distinct weights that add up to twelve groups of equal weight, so that they can
+/// be balanced into both three and four compartments.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    const GROUPS: usize = 12;
+    // A group of one package would have the same weight as every other such group
+    let size = size.max(2 * GROUPS);
+    loop {
+        let mut group_sizes = vec![2; GROUPS];
+        for _ in 2 * GROUPS..size {
+            group_sizes[rng.index(GROUPS)] += 1;
+        }
+        let group_weight = rng.range((size * size / 2) as i64..=(size * size) as i64);
+
+        let mut weights = Vec::with_capacity(size);
+        for &group_size in &group_sizes {
+            let most = (group_weight - 1) / group_size as i64;
+            let mut remaining = group_weight;
+            for _ in 1..group_size {
+                let weight = rng.range(1..=most);
+                weights.push(weight);
+                remaining -= weight;
+            }
+            weights.push(remaining);
+        }
+
+        weights.sort();
+        if weights.windows(2).all(|pair| pair[0] != pair[1]) {
+            return weights.iter().map(|weight| weight.to_string()).collect::<Vec<_>>().join("\n");
+        }
+    }
 }
 
 
@@ -193,7 +259,7 @@ mod tests {
         // Only one element to choose
         let result = combinations_sum_n(&prev, target, &nums, MAX_SIZE);
         assert_eq!(1, result.len());
-        let res1 = result.get(0).unwrap().clone();
+        let res1 = result.first().unwrap().clone();
         assert_eq!(res1, vec![10]);
 
         // No good choices
@@ -207,7 +273,7 @@ mod tests {
         let result = combinations_sum_n(&prev, target, &nums, MAX_SIZE);
         assert_eq!(1, result.len());
 
-        let res1 = result.get(0).unwrap().clone();
+        let res1 = result.first().unwrap().clone();
         assert_eq!(res1, vec![2, 10]);
     }
     
@@ -229,10 +295,11 @@ mod tests {
 
         let result = combinations_sum_n(&prev, target, &nums, MAX_SIZE);
 
-        let mut expecteds: Vec<Vec<u64>> = Vec::new();
-        expecteds.push(vec![10]);
-        expecteds.push(vec![7, 3]);
-        expecteds.push(vec![7, 2, 1]);
+        let expecteds: Vec<Vec<u64>> = vec![
+            vec![10],
+            vec![7, 3],
+            vec![7, 2, 1],
+        ];
 
         assert_eq!(expecteds.len(), result.len());
         for expected in expecteds  {
@@ -249,9 +316,10 @@ mod tests {
 
         let result = combinations_sum_n(&prev, target, &nums, max);
 
-        let mut expecteds: Vec<Vec<u64>> = Vec::new();
-        expecteds.push(vec![12]);
-        expecteds.push(vec![10, 2]);
+        let expecteds: Vec<Vec<u64>> = vec![
+            vec![12],
+            vec![10, 2],
+        ];
 
         assert_eq!(expecteds.len(), result.len());
         for expected in expecteds  {
@@ -1,33 +1,89 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-use regex::Regex;
+use std::io::{ BufRead, Lines };
+
+use crate::cancel;
+use crate::error::{ PuzzleError, Result };
+use crate::examples::Example;
+use crate::generate::{ Generator, Rng };
+use crate::params::{ Param, Params };
+use crate::pattern::Pattern;
+use crate::registry::{ Puzzle, Solver, Tag };
+use crate::solution::{ Answer, Solution };
+
+pub struct Day25;
+
+pub const PUZZLE: Puzzle = Puzzle {
+    day: 25,
+    title: "Let It Snow",
+    tags: &[Tag::Math],
+    examples: EXAMPLES,
+    generator: Generator { size: "largest row and column", default_size: 3000, generate },
+    solver: &Solver::<Day25>::NEW,
+};
+
+/// Examples from the problem statement.
+pub const EXAMPLES: &[Example] = &[
+    Example { part: 1, input: "Enter the code at row 1, column 1.", expected: "20151125", params: &[] },
+    Example { part: 1, input: "Enter the code at row 4, column 2.", expected: "32451966", params: &[] },
+    Example { part: 1, input: "Enter the code at row 2, column 5.", expected: "15514188", params: &[] },
+];
 
 const START_CODE: u64 = 20151125;
 const F: u64 = 252533;
 const MOD: u64 = 33554393;
 
-pub(crate) fn solve(input: Lines<BufReader<File>>) {
-
-    let (row, col) = parse_input(input);
+impl Solution for Day25 {
+    type Input = (u64, u64);
 
+    const PARAMS: &'static [Param] = &[
+        Param::algorithm("How to find the code: generate every code before it, or modular exponentiation", &["iterate", "modular-exponentiation"]),
+    ];
 
-    let part1 = part_1(row, col);
-    println!("Part 1: Input code: {}", part1);
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<(u64, u64)> {
+        parse_input(input)
+    }
 
+    fn part1(&(row, col): &(u64, u64), params: &Params) -> Result<Answer> {
+        let code = match params.algorithm() {
+            "modular-exponentiation" => code_by_exponentiation(row, col),
+            _ => part_1(row, col)?,
+        };
+        Ok(code.into())
+    }
 
-    let part2 = "";
-    println!("Part 2: {}", part2);
+    fn part2(_: &(u64, u64), _params: &Params) -> Result<Answer> {
+        Ok(Answer::Empty)
+    }
 }
 
-fn part_1(row: u64, col: u64) -> u64 {
+fn part_1(row: u64, col: u64) -> Result<u64> {
     let seq_number = convert_row_col_to_sequence_num(row, col);
 
     let mut code = START_CODE;
 
     for _ in 1..seq_number {
+        cancel::check()?;
         code = (code*F) % MOD;
     }
+    Ok(code)
+}
+
+/// The code is the start code multiplied by F once for every code before it,
+/// so it can be found with O(log n) multiplications by squaring.
+fn code_by_exponentiation(row: u64, col: u64) -> u64 {
+    // The diagonal through (row, col) starts after the triangle of earlier diagonals
+    let diagonal = row + col - 1;
+    let seq_number = diagonal * (diagonal - 1) / 2 + col;
+
+    let mut code = START_CODE;
+    let mut factor = F;
+    let mut exponent = seq_number - 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            code = (code * factor) % MOD;
+        }
+        factor = (factor * factor) % MOD;
+        exponent >>= 1;
+    }
     code
 }
 
@@ -44,29 +100,35 @@ fn convert_row_col_to_sequence_num(row: u64, col: u64) -> u64 {
     let mut seq_number = top_of_col;
 
     for i in 0..row-1 {
-        seq_number += col + (i as u64);
+        seq_number += col + i;
     }
-    return seq_number;
+    seq_number
 }
 
-fn parse_input(input: Lines<BufReader<File>>) -> (u64, u64) {
-
-    let re = Regex::new(r"row (?P<row>\d+), column (?P<col>\d+)").unwrap();
-
-    for line in input {
-        let line = line.unwrap();
+const POSITION: Pattern = Pattern::new("{message} row {row}, column {column}.");
 
-        let Some(caps) = re.captures(&line) else { panic!("Unexpected input"); };
-        let row = &caps["row"];
-        let row = row.parse::<u64>().unwrap();
+fn parse_input<R: BufRead>(mut input: Lines<R>) -> Result<(u64, u64)> {
+    let Some(line) = input.next() else {
+        return Err(PuzzleError::invalid_line(1, "", "No input data"));
+    };
+    let line = line?;
+    let fields = POSITION.parse(1, &line)?;
 
-        let col = &caps["col"];
-        let col = col.parse::<u64>().unwrap();
-
-        return (row, col);
+    let mut position = [0; 2];
+    for (value, name) in position.iter_mut().zip(["row", "column"]) {
+        *value = fields.number(name)?;
+        if *value == 0 {
+            return Err(fields.invalid(name, "rows and columns start at 1"));
+        }
     }
 
-    panic!("No input data");
+    Ok((position[0], position[1]))
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1) as i64;
+    format!("To continue, please consult the code grid in the manual.  Enter the code at row {}, column {}.",
+        rng.range(1..=size), rng.range(1..=size))
 }
 
 
@@ -131,8 +193,9 @@ mod tests {
         expecteds.insert((2, 5), 15514188);
 
         for ((row, col), expected) in expecteds {
-            let actual = part_1(row, col);
+            let actual = part_1(row, col).unwrap();
             assert_eq!(expected, actual, "expected ({}, {}) -> {} but was {}", row, col, expected, actual);
+            assert_eq!(expected, code_by_exponentiation(row, col));
         }
     }
         
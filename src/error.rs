@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use std::str::FromStr;
+
+/// Everything that can go wrong while reading, parsing or solving a puzzle.
+#[derive(Debug)]
+pub enum PuzzleError {
+    Io(io::Error),
+    /// The input could not be parsed. Lines and columns start at 1.
+    Parse {
+        line: usize,
+        column: usize,
+        text: String,
+        message: String,
+    },
+    /// The input was parsed, but the puzzle has no solution for it.
+    Unsolvable(String),
+    /// The solve was still running when its timeout ran out.
+    TimedOut(Duration),
+    /// The solve was stopped through its [`CancelToken`](crate::cancel::CancelToken).
+    Cancelled,
+}
+
+pub type Result<T> = std::result::Result<T, PuzzleError>;
+
+impl PuzzleError {
+    /// A parse error pointing at `token` within `text`, the full text of the offending line.
+    pub fn invalid_token(line: usize, text: &str, token: &str, message: impl Into<String>) -> Self {
+        PuzzleError::Parse {
+            line,
+            column: column_of(text, token),
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// A parse error about the offending line as a whole.
+    pub fn invalid_line(line: usize, text: &str, message: impl Into<String>) -> Self {
+        PuzzleError::Parse {
+            line,
+            column: 1,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn unsolvable(message: impl Into<String>) -> Self {
+        PuzzleError::Unsolvable(message.into())
+    }
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::Io(e) => write!(f, "could not read input: {}", e),
+            PuzzleError::Parse { line, column, text, message } => {
+                writeln!(f, "parse error on line {}, column {}: {}", line, column, message)?;
+                writeln!(f, "  | {}", text)?;
+                write!(f, "  | {}^", " ".repeat(column.saturating_sub(1)))
+            },
+            PuzzleError::Unsolvable(message) => write!(f, "no solution: {}", message),
+            PuzzleError::TimedOut(timeout) => write!(f, "timed out after {:.3}s", timeout.as_secs_f64()),
+            PuzzleError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl Error for PuzzleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PuzzleError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PuzzleError {
+    fn from(e: io::Error) -> Self {
+        PuzzleError::Io(e)
+    }
+}
+
+/// Parses `token` (a piece of the line `text`) as a number.
+pub fn parse_number<T: FromStr>(line: usize, text: &str, token: &str) -> Result<T> {
+    token.parse::<T>().map_err(|_| {
+        PuzzleError::invalid_token(line, text, token, format!("expected a number, found '{}'", token))
+    })
+}
+
+/// The 1-based character column at which `token` appears in `text`.
+///
+/// If `token` is a slice of `text` its exact position is used, otherwise this
+/// falls back to the first occurrence of `token`, or column 1 if it is absent.
+fn column_of(text: &str, token: &str) -> usize {
+    let start = text.as_ptr() as usize;
+    let offset = token.as_ptr() as usize;
+
+    let byte_offset = if offset >= start && offset + token.len() <= start + text.len() {
+        offset - start
+    } else {
+        text.find(token).unwrap_or(0)
+    };
+
+    text[..byte_offset].chars().count() + 1
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_of_slice() {
+        let text = "1x1x1x";
+        let tokens: Vec<&str> = text.split('x').collect();
+        assert_eq!(1, column_of(text, tokens[0]));
+        assert_eq!(3, column_of(text, tokens[1]));
+        assert_eq!(5, column_of(text, tokens[2]));
+    }
+
+    #[test]
+    fn test_column_of_copied_token() {
+        let text = "Alice would gain 54 happiness";
+        assert_eq!(18, column_of(text, &String::from("54")));
+        assert_eq!(1, column_of(text, "missing"));
+    }
+
+    #[test]
+    fn test_display_parse_error() {
+        let text = "2x3xfoo";
+        let err = parse_number::<u32>(4, text, &text[4..]).unwrap_err();
+        let expected = "parse error on line 4, column 5: expected a number, found 'foo'\n  | 2x3xfoo\n  |     ^";
+        assert_eq!(expected, err.to_string());
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::Cursor;
+
+use crate::error::Result;
+use crate::params::Params;
+use crate::registry::Puzzle;
+use crate::solution::Answer;
+
+/// An example from a puzzle's problem statement, with the answer it should give.
+///
+/// Each day lists its examples in an `EXAMPLES` constant, which is registered
+/// with its [`Puzzle`]. Examples that only work with different settings than the
+/// real puzzle, such as a weaker player, give them in `params`.
+#[derive(Debug)]
+pub struct Example {
+    pub part: u8,
+    pub input: &'static str,
+    pub expected: &'static str,
+    /// Parameters to set, as `(name, value)`, on top of the ones the example is solved with.
+    pub params: &'static [(&'static str, &'static str)],
+}
+
+impl Example {
+    /// Solves the example's part of the given puzzle.
+    pub fn solve(&self, puzzle: &Puzzle) -> Result<Answer> {
+        self.solve_with(puzzle, &puzzle.params())
+    }
+
+    /// Like [`solve`](Self::solve), but with the given parameters, which should come
+    /// from [`Puzzle::params`]. The example's own parameters override them.
+    ///
+    /// # Panics
+    ///
+    /// If the example sets a parameter the puzzle does not declare.
+    pub fn solve_with(&self, puzzle: &Puzzle, params: &Params) -> Result<Answer> {
+        let mut params = params.clone();
+        for (name, value) in self.params {
+            params.set(name, value).unwrap_or_else(|e| panic!("invalid example parameter: {}", e));
+        }
+        let run = puzzle.run_with(Cursor::new(self.input), Some(self.part), &params)?.into_result()?;
+        let (_, part_run) = run.solved().next().expect("the requested part was solved");
+        Ok(part_run.answer.clone())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::params::ALGORITHM;
+    use crate::registry;
+
+    fn check_examples(day: u32) {
+        let puzzle = registry::get(day).unwrap();
+        // Every algorithm must solve the examples, not just the default one
+        let mut all_params = vec![puzzle.params()];
+        for algorithm in puzzle.algorithms().iter().skip(1) {
+            let mut params = puzzle.params();
+            params.set(ALGORITHM, algorithm).unwrap();
+            all_params.push(params);
+        }
+
+        for params in &all_params {
+            for (i, example) in puzzle.examples.iter().enumerate() {
+                match example.solve_with(puzzle, params) {
+                    Ok(answer) => assert_eq!(example.expected, answer.to_string(),
+                        "day {} part {} example {} with {:?}: {:?}", day, example.part, i+1, params, example.input),
+                    Err(e) => panic!("day {} part {} example {} with {:?} failed: {}", day, example.part, i+1, params, e),
+                }
+            }
+        }
+    }
+
+    macro_rules! example_tests {
+        ($($name:ident: $day:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    check_examples($day);
+                }
+            )*
+        };
+    }
+
+    example_tests! {
+        examples_day01: 1,
+        examples_day02: 2,
+        examples_day03: 3,
+        examples_day04: 4,
+        examples_day05: 5,
+        examples_day06: 6,
+        examples_day07: 7,
+        examples_day08: 8,
+        examples_day09: 9,
+        examples_day10: 10,
+        examples_day11: 11,
+        examples_day12: 12,
+        examples_day13: 13,
+        examples_day14: 14,
+        examples_day15: 15,
+        examples_day16: 16,
+        examples_day17: 17,
+        examples_day18: 18,
+        examples_day19: 19,
+        examples_day20: 20,
+        examples_day21: 21,
+        examples_day22: 22,
+        examples_day23: 23,
+        examples_day24: 24,
+        examples_day25: 25,
+    }
+}
@@ -0,0 +1,155 @@
+use std::ops::RangeInclusive;
+
+/// A small pseudo-random number generator (SplitMix64). It is written out here
+/// rather than taken from a crate so that a seed gives the same input on every
+/// platform and with every version of the dependencies.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (low, high) = range.into_inner();
+        assert!(low <= high, "cannot pick a number from an empty range");
+        let span = high.abs_diff(low).wrapping_add(1);
+        if span == 0 {
+            // The range covers every i64
+            return self.next_u64() as i64;
+        }
+        low.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    /// An index into a collection of `len` items, which must not be empty.
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "cannot pick an index into an empty collection");
+        (self.next_u64() % len as u64) as usize
+    }
+
+    /// True with a probability of `numerator` in `denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    /// A string of `len` random lowercase letters.
+    pub fn letters(&mut self, len: usize) -> String {
+        (0..len).map(|_| (b'a' + self.index(26) as u8) as char).collect()
+    }
+}
+
+/// Produces random inputs for a day's puzzle, for stress-testing its solver.
+///
+/// Every generated input is valid, and as far as possible has a solution, so
+/// that failures point at the solver rather than the input.
+pub struct Generator {
+    /// What the size of an input controls, for help text.
+    pub size: &'static str,
+    /// The size of a real puzzle input, or a smaller one if every algorithm cannot
+    /// solve a real-sized input quickly.
+    pub default_size: usize,
+    pub generate: fn(&mut Rng, usize) -> String,
+}
+
+impl Generator {
+    /// An input of the given size, or of the default size if `size` is `None`.
+    pub fn generate(&self, seed: u64, size: Option<usize>) -> String {
+        let mut rng = Rng::new(seed);
+        (self.generate)(&mut rng, size.unwrap_or(self.default_size))
+    }
+}
+
+/// Names for the people, places and things in generated inputs.
+pub const NAMES: &[&str] = &[
+    "Alice", "Bob", "Carol", "David", "Eric", "Frank", "George", "Mallory",
+    "Comet", "Cupid", "Dancer", "Dasher", "Donner", "Prancer", "Rudolph", "Vixen",
+    "Arbre", "Faerun", "Norrath", "Snowdin", "Straylight", "Tambi", "Tristram", "AlphaCentauri",
+];
+
+/// `count` distinct names: the first from [`NAMES`], in random order, followed by
+/// numbered ones if there are not enough.
+pub fn names(rng: &mut Rng, count: usize) -> Vec<String> {
+    let mut names: Vec<String> = NAMES.iter().map(|name| name.to_string()).collect();
+    rng.shuffle(&mut names);
+    names.truncate(count);
+    for i in names.len()..count {
+        names.push(format!("{}{}", NAMES[i % NAMES.len()], i / NAMES.len() + 1));
+    }
+    names
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use crate::runner::Run;
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let a: Vec<u64> = (0..5).scan(Rng::new(42), |rng, _| Some(rng.next_u64())).collect();
+        let b: Vec<u64> = (0..5).scan(Rng::new(42), |rng, _| Some(rng.next_u64())).collect();
+        let c: Vec<u64> = (0..5).scan(Rng::new(43), |rng, _| Some(rng.next_u64())).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.range(-3..=3)));
+        }
+        assert_eq!(5, rng.range(5..=5));
+    }
+
+    #[test]
+    fn test_names_are_distinct() {
+        let names = names(&mut Rng::new(1), 30);
+        let mut unique = names.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(30, unique.len());
+    }
+
+    #[test]
+    fn test_generators_are_deterministic() {
+        for puzzle in registry::all() {
+            let generator = &puzzle.generator;
+            assert_eq!(generator.generate(3, Some(5)), generator.generate(3, Some(5)), "day {}", puzzle.day);
+        }
+    }
+
+    #[test]
+    fn test_generated_inputs_are_solved() {
+        // Day 4 needs millions of hashes, which takes too long in a debug build
+        for puzzle in registry::all().filter(|puzzle| puzzle.day != 4) {
+            let input = puzzle.generator.generate(1, Some(6));
+            if let Err(e) = puzzle.run(input.as_bytes(), None).and_then(Run::into_result) {
+                panic!("day {} failed on a generated input: {}\n{}", puzzle.day, e, input);
+            }
+        }
+    }
+}
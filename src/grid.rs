@@ -0,0 +1,368 @@
+use std::fmt;
+use std::io::{ BufRead, Lines };
+use std::ops::{ Add, Index, IndexMut };
+
+use crate::error::{ PuzzleError, Result };
+
+/// A position on a grid, or on an unbounded plane. `x` is the column, counting
+/// right, and `y` is the row, counting down from the top.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point { x, y }
+    }
+
+    /// The point one step away in `direction`.
+    pub fn step(self, direction: Direction) -> Point {
+        self + direction.offset()
+    }
+
+    /// The four points that share an edge with this one.
+    pub fn neighbors4(self) -> impl Iterator<Item = Point> {
+        NEIGHBORS_4.iter().map(move |&offset| self + offset)
+    }
+
+    /// The eight points that share an edge or a corner with this one.
+    pub fn neighbors8(self) -> impl Iterator<Item = Point> {
+        NEIGHBORS_8.iter().map(move |&offset| self + offset)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+const NEIGHBORS_4: [Point; 4] = [
+    Point::new(0, -1),
+    Point::new(-1, 0),
+    Point::new(1, 0),
+    Point::new(0, 1),
+];
+
+const NEIGHBORS_8: [Point; 8] = [
+    Point::new(-1, -1),
+    Point::new(0, -1),
+    Point::new(1, -1),
+    Point::new(-1, 0),
+    Point::new(1, 0),
+    Point::new(-1, 1),
+    Point::new(0, 1),
+    Point::new(1, 1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// The direction an arrow like `^` or `>` points in.
+    pub fn from_arrow(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::Up),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            '>' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+            Direction::Right => Point::new(1, 0),
+        }
+    }
+}
+
+/// The points from `min` to `max`, including both corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rect {
+    pub const fn new(min: Point, max: Point) -> Self {
+        Rect { min, max }
+    }
+
+    pub fn width(&self) -> usize {
+        usize::try_from(self.max.x - self.min.x + 1).unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        usize::try_from(self.max.y - self.min.y + 1).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    /// Every point in the rectangle, a row at a time from the top left.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        (self.min.y..=self.max.y).flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Point::new(x, y)))
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} through {}", self.min, self.max)
+    }
+}
+
+/// A rectangular grid of cells, stored a row at a time. The top left cell is at
+/// [`Point::ORIGIN`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    /// A grid from its cells, a row at a time. Panics if there are not
+    /// `width * height` of them.
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "a {}x{} grid needs {} cells", width, height, width * height);
+        Grid { width, height, cells }
+    }
+
+    /// Parses a map with one character per cell, such as `#` and `.` for lights,
+    /// using `cell` to read each character. Every row must be as long as the first,
+    /// and `expected` describes the characters `cell` accepts, for error messages.
+    pub fn parse<R: BufRead>(input: Lines<R>, cell: impl Fn(char) -> Option<T>, expected: &str) -> Result<Self> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for (i, line) in input.enumerate() {
+            let line = line?;
+            let row_width = line.chars().count();
+            if height == 0 {
+                width = row_width;
+            } else if row_width != width {
+                return Err(PuzzleError::invalid_line(i+1, &line, format!("expected {} cells, like the first row", width)));
+            }
+            height += 1;
+
+            for (pos, c) in line.char_indices() {
+                match cell(c) {
+                    Some(value) => cells.push(value),
+                    None => return Err(PuzzleError::invalid_token(i+1, &line, &line[pos..pos+c.len_utf8()], format!("expected {}", expected))),
+                }
+            }
+        }
+
+        if width == 0 {
+            return Err(PuzzleError::invalid_line(1, "", "expected a grid"));
+        }
+        Ok(Grid { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The rectangle covering the whole grid.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(Point::ORIGIN, Point::new(self.width as i64 - 1, self.height as i64 - 1))
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.bounds().contains(p)
+    }
+
+    fn index_of(&self, p: Point) -> Option<usize> {
+        self.contains(p).then(|| p.y as usize * self.width + p.x as usize)
+    }
+
+    /// The cell at `p`, or `None` if it is outside the grid.
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index_of(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index_of(p).map(|i| &mut self.cells[i])
+    }
+
+    /// The cell at `p`, wrapping around the edges, so that the column left of the
+    /// first is the last.
+    pub fn get_wrapping(&self, p: Point) -> &T {
+        let x = p.x.rem_euclid(self.width as i64);
+        let y = p.y.rem_euclid(self.height as i64);
+        &self[Point::new(x, y)]
+    }
+
+    /// Every cell with its position, a row at a time from the top left.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.bounds().points().zip(&self.cells)
+    }
+
+    /// Every cell, a row at a time from the top left.
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// The cells next to `p` along an edge, with their positions, leaving out the
+    /// ones outside the grid.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbors4().filter_map(move |n| self.get(n).map(|cell| (n, cell)))
+    }
+
+    /// The cells next to `p` along an edge or a corner, with their positions,
+    /// leaving out the ones outside the grid.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        p.neighbors8().filter_map(move |n| self.get(n).map(|cell| (n, cell)))
+    }
+
+    /// The cells in `rect`, a row at a time. Panics if it does not fit in the grid.
+    pub fn rect_mut(&mut self, rect: Rect) -> impl Iterator<Item = &mut T> {
+        assert!(rect.is_empty() || (self.contains(rect.min) && self.contains(rect.max)), "{} is outside the grid", rect);
+        let (x, width) = (rect.min.x.max(0) as usize, rect.width());
+        self.cells.chunks_mut(self.width)
+            .skip(rect.min.y.max(0) as usize)
+            .take(rect.height())
+            .flat_map(move |row| &mut row[x..x + width])
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Draws the grid with one character per cell, as read by [`parse`](Self::parse).
+    pub fn render(&self, cell: impl Fn(&T) -> char) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&cell).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    /// Panics if `p` is outside the grid.
+    fn index(&self, p: Point) -> &T {
+        self.get(p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, width, height))
+    }
+}
+
+/// Shows the size rather than every cell, which would be unreadable for a big grid.
+impl<T> fmt::Debug for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grid")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        Grid::parse("123\n456".as_bytes().lines(), |c| c.to_digit(10).map(|d| d as u8), "a digit").unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let grid = grid();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(6, grid[Point::new(2, 1)]);
+        assert_eq!("123\n456", grid.render(|&d| char::from(b'0' + d)));
+
+        let ragged = Grid::parse("12\n3".as_bytes().lines(), |c| c.to_digit(10), "a digit");
+        assert!(ragged.unwrap_err().to_string().contains("expected 2 cells, like the first row"));
+        let invalid = Grid::parse("1x".as_bytes().lines(), |c| c.to_digit(10), "a digit");
+        assert!(invalid.unwrap_err().to_string().contains("column 2: expected a digit"));
+    }
+
+    #[test]
+    fn test_access() {
+        let grid = grid();
+        assert_eq!(Some(&1), grid.get(Point::ORIGIN));
+        assert_eq!(None, grid.get(Point::new(3, 0)));
+        assert_eq!(None, grid.get(Point::new(0, -1)));
+        assert_eq!(&3, grid.get_wrapping(Point::new(-1, 0)));
+        assert_eq!(&4, grid.get_wrapping(Point::new(3, -1)));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = grid();
+        let values = |neighbors: Vec<(Point, &u8)>| neighbors.into_iter().map(|(_, &v)| v).collect::<Vec<u8>>();
+        assert_eq!(vec![2, 4], values(grid.neighbors4(Point::ORIGIN).collect()));
+        assert_eq!(vec![2, 4, 6], values(grid.neighbors4(Point::new(1, 1)).collect()));
+        assert_eq!(vec![1, 2, 3, 4, 6], values(grid.neighbors8(Point::new(1, 1)).collect()));
+        assert_eq!(8, Point::ORIGIN.neighbors8().count());
+    }
+
+    #[test]
+    fn test_rect() {
+        let rect = Rect::new(Point::new(1, 0), Point::new(2, 1));
+        assert_eq!(vec![Point::new(1, 0), Point::new(2, 0), Point::new(1, 1), Point::new(2, 1)], rect.points().collect::<Vec<_>>());
+
+        let mut grid = grid();
+        for cell in grid.rect_mut(rect) {
+            *cell = 0;
+        }
+        assert_eq!("100\n400", grid.render(|&d| char::from(b'0' + d)));
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::{ BufRead, Lines };
+
+use crate::error::Result;
+
+/// How a day's raw input is cleaned up before it is parsed, so that inputs saved
+/// on Windows or copied from a browser parse the same as the originals.
+///
+/// Each day chooses its normalization with [`Solution::NORMALIZE`](crate::Solution::NORMALIZE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalize {
+    /// Remove a byte order mark from the start of the input.
+    pub strip_bom: bool,
+    /// Turn `\r\n` and lone `\r` line endings into `\n`.
+    pub line_endings: bool,
+    /// Remove whitespace from the end of every line, and blank lines from the end of the input.
+    pub trim_trailing: bool,
+    /// Leave out every blank line. They are skipped as the lines are read, with
+    /// [`lines`](Self::lines), so that line numbers in parse errors match the file.
+    pub drop_blank_lines: bool,
+}
+
+impl Normalize {
+    /// What most days use: everything except dropping blank lines.
+    pub const DEFAULT: Normalize = Normalize {
+        strip_bom: true,
+        line_endings: true,
+        trim_trailing: true,
+        drop_blank_lines: false,
+    };
+
+    /// Leave the input exactly as it is.
+    pub const NONE: Normalize = Normalize {
+        strip_bom: false,
+        line_endings: false,
+        trim_trailing: false,
+        drop_blank_lines: false,
+    };
+
+    pub fn apply(&self, text: &str) -> String {
+        let text = match text.strip_prefix('\u{feff}') {
+            Some(rest) if self.strip_bom => rest,
+            _ => text,
+        };
+        let text = if self.line_endings {
+            text.replace("\r\n", "\n").replace('\r', "\n")
+        } else {
+            text.to_string()
+        };
+
+        if !self.trim_trailing {
+            return text;
+        }
+
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        for line in lines.iter_mut() {
+            *line = line.trim_end();
+        }
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// The lines of the input, each with its line number in the file, counting
+    /// from 1. Blank lines are left out if [`drop_blank_lines`](Self::drop_blank_lines) is set.
+    pub fn lines<R: BufRead>(&self, input: Lines<R>) -> impl Iterator<Item = Result<(usize, String)>> {
+        let drop_blank_lines = self.drop_blank_lines;
+        input.enumerate()
+            .map(|(i, line)| Ok((i+1, line?)))
+            .filter(move |line| !(drop_blank_lines && matches!(line, Ok((_, text)) if text.trim().is_empty())))
+    }
+}
+
+impl Default for Normalize {
+    fn default() -> Self {
+        Normalize::DEFAULT
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let text = "\u{feff}one  \r\ntwo\rthree\t\n\nfour\r\n\r\n  \n";
+        assert_eq!("one\ntwo\nthree\n\nfour", Normalize::DEFAULT.apply(text));
+    }
+
+    #[test]
+    fn test_drop_blank_lines() {
+        let normalize = Normalize { drop_blank_lines: true, ..Normalize::DEFAULT };
+        let text = normalize.apply("\n1\n\n2\r\n   \r\n3\n");
+        let lines: Vec<(usize, String)> = normalize.lines(text.as_bytes().lines()).collect::<Result<_>>().unwrap();
+        assert_eq!(vec![(2, String::from("1")), (4, String::from("2")), (6, String::from("3"))], lines);
+
+        let lines = Normalize::DEFAULT.lines(text.as_bytes().lines()).count();
+        assert_eq!(6, lines);
+    }
+
+    #[test]
+    fn test_keeps_leading_whitespace() {
+        assert_eq!("  a\n\tb", Normalize::DEFAULT.apply("  a \n\tb\n"));
+    }
+
+    #[test]
+    fn test_none() {
+        let text = "\u{feff}one  \r\ntwo\r\n\n";
+        assert_eq!(text, Normalize::NONE.apply(text));
+    }
+}
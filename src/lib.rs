@@ -0,0 +1,56 @@
+//! Solutions to the Advent of Code 2015 puzzles.
+//!
+//! Every day exposes a type implementing [`Solution`], so answers can be
+//! computed (and checked) from Rust as well as from the command line.
+
+pub mod batch;
+pub mod bench;
+pub mod cancel;
+pub mod crosscheck;
+pub mod error;
+pub mod examples;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod params;
+pub mod pattern;
+pub mod pool;
+pub mod registry;
+pub mod repl;
+pub mod report;
+pub mod runner;
+pub mod search;
+pub mod server;
+pub mod solution;
+pub mod verify;
+pub mod watch;
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+pub use error::PuzzleError;
+pub use params::Params;
+pub use solution::{ Answer, Solution, Visual };
@@ -1,81 +1,1137 @@
 extern crate lapp;
-use std::fs::File;
-use std::io::{ self, BufRead, BufReader };
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+use std::env;
+use std::fs::{ self, File };
+use std::io::{ self, BufRead, BufReader, Cursor, Write };
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
+use std::time::{ Duration, Instant };
 
-fn main() {
+use advent_of_code_2015::{ batch, bench, cancel, crosscheck, pool, registry, report, server, Params, PuzzleError };
+use advent_of_code_2015::batch::BatchResult;
+use advent_of_code_2015::params::ALGORITHM;
+use advent_of_code_2015::registry::{ Puzzle, Tag };
+use advent_of_code_2015::repl::Session;
+use advent_of_code_2015::runner::{ PartRun, Run };
+use advent_of_code_2015::verify::{ ExpectedAnswers, Verdict };
+use advent_of_code_2015::watch::{ Change, FileWatcher, POLL_INTERVAL };
 
-    let args = lapp::parse_args("
+const USAGE: &str = "
 Run solutions to Advent Of Code 2015.
   <day> (integer) Which day's challenge to solve
-  <file> (string) The input file to use
-    ");
+  <file> (default '-') The input file to use, or - to read from stdin
+  --input (default '') Use this text as the input instead of reading a file
+  -p, --part (default 'both') Which part to solve: 1, 2 or both
+  -P, --param... (string) Override one of the day's parameters, as name=value
+  -a, --algorithm (default '') Solve with another of the day's algorithms (see --help-day)
+  -t, --timeout (float default 0) Give up after this many seconds, or 0 for no limit
+  -f, --format (default 'text') Output format: text, or json for one JSON object per part
+  -w, --watch Solve again whenever the input file changes, showing how the answers changed
+
+Use `--help-day <day>` to see a day's parameters and algorithms.
+Use `all` instead of a day to solve every day (see `all --help`),
+`verify` to check the answers against known ones (see `verify --help`),
+`examples` to check the examples from the problem statements (see `examples --help`),
+`batch` to solve one day for many input files (see `batch --help`),
+`bench` to time a day over repeated runs (see `bench --help`),
+`dump` to show what a day's input is parsed into (see `dump --help`),
+`generate` to make a random input for a day (see `generate --help`),
+`crosscheck` to compare a day's algorithms on random inputs (see `crosscheck --help`),
+`repl` to explore one day's input interactively (see `repl --help`),
+`serve` to solve puzzles over HTTP (see `serve --help`),
+`report` to write a Markdown or HTML report of every day (see `report --help`),
+or `list` to show every day's title and tags (see `list --help`).
+";
+
+const ALL_USAGE: &str = "
+Solve every day, reading inputs from dayNN.txt files in a directory.
+  -i, --inputs (default 'inputs') The directory containing the input files
+  -p, --part (default 'both') Which part to solve: 1, 2 or both
+  -j, --jobs (integer default 0) How many days to solve at once, or 0 for one per core
+  -t, --timeout (float default 0) Give up on a day after this many seconds, or 0 for no limit
+  -f, --format (default 'text') Output format: text, or json for one JSON object per part
+";
+
+const VERIFY_USAGE: &str = "
+Solve every day and compare the results with a file of known answers.
+Exits with a non-zero status if any answer does not match.
+  -a, --answers (default 'answers.toml') The file of expected answers
+  -i, --inputs (default 'inputs') The directory containing the input files
+  -p, --part (default 'both') Which part to solve: 1, 2 or both
+  -j, --jobs (integer default 0) How many days to solve at once, or 0 for one per core
+  -t, --timeout (float default 0) Give up on a day after this many seconds, or 0 for no limit
+  -f, --format (default 'text') Output format: text, or json for one JSON object per part
+";
+
+const EXAMPLES_USAGE: &str = "
+Solve the examples from the problem statements and check their answers.
+Exits with a non-zero status if any example fails.
+  -d, --day (integer default 0) Only run the examples for this day
+  -f, --format (default 'text') Output format: text, or json for one JSON object per example
+";
+
+const BATCH_USAGE: &str = "
+Solve one day for each of many input files, in parallel, and print a table of the answers.
+Exits with a non-zero status if any input fails.
+  <day> (integer) Which day's challenge to solve
+  <inputs> (string...) Input files, directories of them, or file name patterns with * and ?
+  -p, --part (default 'both') Which part to solve: 1, 2 or both
+  -P, --param... (string) Override one of the day's parameters, as name=value
+  -a, --algorithm (default '') Solve with another of the day's algorithms (see --help-day)
+  -j, --jobs (integer default 0) How many inputs to solve at once, or 0 for one per core
+  -t, --timeout (float default 0) Give up on an input after this many seconds, or 0 for no limit
+  -f, --format (default 'text') Output format: text, or json for one JSON object per part
+";
+
+const BENCH_USAGE: &str = "
+Solve one day repeatedly and report timing statistics for each phase.
+The input is read once and parsed from memory on every run.
+  <day> (integer) Which day's challenge to solve
+  <file> (default '-') The input file to use, or - to read from stdin
+  --input (default '') Use this text as the input instead of reading a file
+  -n, --runs (integer default 10) How many runs to measure
+  -w, --warmup (integer default 1) How many runs to do first without measuring
+  -p, --part (default 'both') Which part to solve: 1, 2 or both
+  -P, --param... (string) Override one of the day's parameters, as name=value
+  -a, --algorithm (default '') Solve with another of the day's algorithms (see --help-day)
+  -f, --format (default 'text') Output format: text, or json for one JSON object per phase
+";
+
+const DUMP_USAGE: &str = "
+Parse one day's input without solving it, and print what it was parsed into.
+  <day> (integer) Which day's input to parse
+  <file> (default '-') The input file to use, or - to read from stdin
+  --input (default '') Use this text as the input instead of reading a file
+";
+
+const GENERATE_USAGE: &str = "
+Print a random input for one day, for stress-testing its solver.
+The same seed and size always give the same input.
+  <day> (integer) Which day to generate an input for
+  -s, --seed (integer default 0) The seed for the random generator
+  -n, --size (integer default 0) How big to make the input, or 0 for the default size (see --help-day)
+";
+
+const CROSSCHECK_USAGE: &str = "
+Solve random inputs with every algorithm of the days that have more than one,
+and report any answers that differ.
+Exits with a non-zero status if any do.
+  -d, --day (integer default 0) Only check this day
+  -c, --count (integer default 20) How many inputs to generate for each day
+  -s, --seed (integer default 0) The seed of the first input; the others follow it
+  -n, --size (integer default 0) How big to make the inputs, or 0 for the default size of each day
+  -t, --timeout (float default 0) Leave out an input if an algorithm takes more than this many seconds, or 0 for no limit
+  -f, --format (default 'text') Output format: text, or json for one JSON object per day
+";
+
+const REPL_USAGE: &str = "
+Parse one day's input once, then read commands to solve it, change its parameters
+and ask the day's own questions about it. Type help for the commands.
+  <day> (integer) Which day's challenge to explore
+  <file> (default '-') The input file to use (stdin is for the commands)
+  --input (default '') Use this text as the input instead of reading a file
+  -P, --param... (string) Override one of the day's parameters, as name=value
+  -a, --algorithm (default '') Solve with another of the day's algorithms (see --help-day)
+  -t, --timeout (float default 0) Give up on a command after this many seconds, or 0 for no limit
+";
+
+const SERVE_USAGE: &str = "
+Serve the solvers as a JSON API over HTTP.
+GET /days lists the puzzles, GET /days/<n> describes one, and POST /days/<n>
+solves one with the request body as its input. Add ?part=1 or 2 to solve only
+one part, and any of the day's parameters as name=value.
+  -p, --port (integer default 2015) The port to listen on
+  -H, --host (default '127.0.0.1') The address to listen on
+  -t, --timeout (float default 0) Give up on a solve after this many seconds, or 0 for no limit
+";
+
+const REPORT_USAGE: &str = "
+Solve every day and write a report of the answers, timings and algorithms, with
+pictures of the solutions for the days that have them.
+  -i, --inputs (default 'inputs') The directory containing the input files
+  -o, --output (default 'report.md') The file to write the report to
+  -f, --format (default '') Report format: markdown or html, or by default from the output file's extension
+  -t, --timeout (float default 0) Give up on a day after this many seconds, or 0 for no limit
+";
+
+const HELP_DAY_USAGE: &str = "
+Describe one day's puzzle, including the parameters that can be given with --param.
+  <day> (integer) Which day to describe
+";
+
+const LIST_USAGE: &str = "
+List the days that have solutions, with their titles and tags.
+  -t, --tag (default '') Only list the days with this tag
+  -f, --format (default 'text') Output format: text, or json for one JSON object per day
+";
+
+#[derive(PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// What happened when solving one day.
+enum Outcome {
+    Solved(Run),
+    /// The input file could not be opened.
+    Skipped(String),
+    Failed(PuzzleError),
+}
+
+/// The outcome for one day, along with the input file it used.
+struct DayResult {
+    day: u32,
+    input: String,
+    outcome: Outcome,
+}
+
+fn main() {
+    match env::args().nth(1).as_deref() {
+        Some("all") => solve_all(),
+        Some("verify") => verify(),
+        Some("examples") => check_examples(),
+        Some("batch") => solve_batch(),
+        Some("bench") => bench(),
+        Some("list") => list(),
+        Some("dump") => dump(),
+        Some("generate") => generate(),
+        Some("crosscheck") => run_crosscheck(),
+        Some("repl") => repl(),
+        Some("serve") => serve(),
+        Some("report") => write_report(),
+        Some("--help-day") => help_day(),
+        _ => solve_day(),
+    }
+}
+
+fn solve_day() {
+    let args = lapp::parse_args(USAGE);
+
+    let format = parse_format(&args);
+    let part = parse_part(&args);
+    let puzzle = parse_day(&args);
+    let day = puzzle.day;
+    let params = parse_params(&args, puzzle);
+    let timeout = parse_timeout(&args);
+    if args.get_bool("watch") {
+        let file = args.get_string("file");
+        if file == "-" || !args.get_string("input").is_empty() {
+            args.quit("--watch needs an input file");
+        }
+        watch_day(puzzle, &file, part, &params, timeout, format);
+    }
+    let (input_name, input) = input_source(&args);
+
+    if format == Format::Text {
+        println!("Solving day {} ({}) with input {}", day, puzzle.title, input_name);
+    }
+
+    let result = input
+        .map_err(PuzzleError::from)
+        .and_then(|input| cancel::with_timeout(timeout, || puzzle.run_with(input, part, &params)));
+
+    match result {
+        Ok(run) => {
+            match format {
+                Format::Text => {
+                    for (part, part_run) in run.parts() {
+                        match part_run {
+                            Ok(part_run) => println!("Part {}: {}", part, part_run.answer),
+                            Err(e) => eprintln!("error: part {}: {}", part, e),
+                        }
+                    }
+                },
+                Format::Json => print_json(&run.to_json(day, &input_name)),
+            }
+            if run.error().is_some() {
+                process::exit(1);
+            }
+        },
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        },
+    }
+}
+
+/// Solves the day every time `file` changes, until the process is stopped. Each
+/// answer is compared with the one from the last run that succeeded.
+fn watch_day(puzzle: &Puzzle, file: &str, part: Option<u8>, params: &Params, timeout: Option<Duration>, format: Format) -> ! {
+    let day = puzzle.day;
+    let mut watcher = FileWatcher::new(file);
+    let mut previous: Option<Run> = None;
+
+    loop {
+        watcher.wait(POLL_INTERVAL);
+        if format == Format::Text {
+            println!("Solving day {} ({}) with input {}", day, puzzle.title, file);
+        }
+
+        let result = open_input(file)
+            .map_err(PuzzleError::from)
+            .and_then(|input| cancel::with_timeout(timeout, || puzzle.run_with(input, part, params)));
+
+        match result {
+            Ok(run) => {
+                match format {
+                    Format::Text => {
+                        for (part, part_run) in run.parts() {
+                            let part_run = match part_run {
+                                Ok(part_run) => part_run,
+                                Err(e) => {
+                                    eprintln!("error: part {}: {}", part, e);
+                                    continue;
+                                },
+                            };
+                            let change = match Change::between(previous.as_ref(), part, &part_run.answer) {
+                                Change::New => String::new(),
+                                Change::Unchanged => String::from(" (unchanged)"),
+                                Change::Changed { was } => format!(" (was {})", was),
+                            };
+                            println!("Part {}: {}{}", part, part_run.answer, change);
+                        }
+                    },
+                    Format::Json => {
+                        for ((part, part_run), mut record) in run.parts().zip(run.to_json(day, file)) {
+                            let Ok(part_run) = part_run else {
+                                println!("{}", record);
+                                continue;
+                            };
+                            let change = Change::between(previous.as_ref(), part, &part_run.answer);
+                            record["previous"] = change.previous(&part_run.answer).map_or(serde_json::Value::Null, serde_json::Value::from);
+                            record["changed"] = serde_json::Value::from(change != Change::Unchanged);
+                            println!("{}", record);
+                        }
+                    },
+                }
+                if run.error().is_none() {
+                    previous = Some(run);
+                }
+            },
+            Err(e) => eprintln!("error: {}", e),
+        }
+
+        if format == Format::Text {
+            println!("Watching {} for changes (press Ctrl-C to stop)", file);
+        }
+    }
+}
+
+fn check_examples() {
+    let mut args = lapp::Args::new(EXAMPLES_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let puzzles: Vec<&Puzzle> = match args.get_integer("day") {
+        0 => registry::all().collect(),
+        _ => vec![parse_day(&args)],
+    };
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for puzzle in puzzles {
+        let day = puzzle.day;
+        for (i, example) in puzzle.examples.iter().enumerate() {
+            let result = example.solve(puzzle);
+            let verdict = match &result {
+                Ok(answer) if answer.to_string() == example.expected => Verdict::Pass,
+                _ => Verdict::Fail,
+            };
+            match verdict {
+                Verdict::Pass => passed += 1,
+                _ => failed += 1,
+            }
+
+            match format {
+                Format::Text => {
+                    let label = format!("Day {:>2} part {} example {}", day, example.part, i+1);
+                    match &result {
+                        Ok(answer) if verdict == Verdict::Pass => println!("{}: {} ({})", label, verdict, answer),
+                        Ok(answer) => println!("{}: {} (expected {}, got {})", label, verdict, example.expected, answer),
+                        Err(e) => println!("{}: {} error: {}", label, verdict, e),
+                    }
+                },
+                Format::Json => {
+                    let answer = match &result {
+                        Ok(answer) => serde_json::Value::from(answer),
+                        Err(e) => serde_json::Value::from(e.to_string()),
+                    };
+                    println!("{}", serde_json::json!({
+                        "day": day,
+                        "part": example.part,
+                        "example": i+1,
+                        "input": example.input,
+                        "expected": example.expected,
+                        "answer": answer,
+                        "status": verdict.to_string(),
+                    }));
+                },
+            }
+        }
+    }
+
+    if format == Format::Text {
+        println!("{} passed, {} failed", passed, failed);
+    }
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn solve_batch() {
+    let mut args = lapp::Args::new(BATCH_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let part = parse_part(&args);
+    let puzzle = parse_day(&args);
+    let day = puzzle.day;
+    let params = parse_params(&args, puzzle);
+    let timeout = parse_timeout(&args);
+
+    let mut paths = Vec::new();
+    for pattern in args.get_strings("inputs") {
+        match batch::find_inputs(&pattern) {
+            Ok(found) if found.is_empty() => args.quit(&format!("no input files match {}", pattern)),
+            Ok(found) => paths.extend(found),
+            Err(e) => args.quit(&format!("cannot read {}: {}", pattern, e)),
+        }
+    }
+
+    let jobs = parse_jobs(&args);
+    let results = batch::solve(puzzle, &paths, part, &params, timeout, jobs);
+    let failed = results.iter()
+        .filter(|result| result.outcome.as_ref().map_or(true, |run| run.error().is_some()))
+        .count();
+
+    match format {
+        Format::Text => {
+            println!("Solving day {} ({}) with {} inputs", day, puzzle.title, results.len());
+            print_batch_table(&results);
+            println!("{} of {} inputs solved", results.len() - failed, results.len());
+        },
+        Format::Json => {
+            for result in &results {
+                let input = result.path.display().to_string();
+                match &result.outcome {
+                    Ok(run) => print_json(&run.to_json(day, &input)),
+                    Err(e) => println!("{}", serde_json::json!({ "day": day, "input": input, "error": e.to_string() })),
+                }
+            }
+        },
+    }
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn bench() {
+    let mut args = lapp::Args::new(BENCH_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let part = parse_part(&args);
+    let puzzle = parse_day(&args);
+    let day = puzzle.day;
+    let params = parse_params(&args, puzzle);
+    let runs = usize::try_from(args.get_integer("runs"))
+        .ok()
+        .filter(|&runs| runs > 0)
+        .unwrap_or_else(|| args.quit("--runs must be at least 1"));
+    let warmup = usize::try_from(args.get_integer("warmup"))
+        .unwrap_or_else(|_| args.quit("--warmup cannot be negative"));
+    let (input_name, input) = input_source(&args);
+
+    let mut text = String::new();
+    let result = input
+        .and_then(|mut input| input.read_to_string(&mut text))
+        .map_err(PuzzleError::from)
+        .and_then(|_| bench::bench(puzzle, &text, part, &params, warmup, runs));
+
+    let bench = match result {
+        Ok(bench) => bench,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        },
+    };
+
+    match format {
+        Format::Text => {
+            println!("Day {} with input {}: {} runs after {} warm-up", day, input_name, runs, warmup);
+            println!("{:<6} | {:>10} | {:>10} | {:>10} | {:>10}", "Phase", "Min", "Median", "Mean", "Std dev");
+            println!("{}", "-".repeat(6 + 4 * (3 + 10)));
+            for (phase, stats) in bench.phases() {
+                let phase = match phase {
+                    "parse" => "Parse",
+                    "part1" => "Part 1",
+                    _ => "Part 2",
+                };
+                println!("{:<6} | {:>10} | {:>10} | {:>10} | {:>10}",
+                    phase,
+                    format_duration(stats.min),
+                    format_duration(stats.median),
+                    format_duration(stats.mean),
+                    format_duration(stats.stddev));
+            }
+        },
+        Format::Json => {
+            let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+            for (phase, stats) in bench.phases() {
+                println!("{}", serde_json::json!({
+                    "day": day,
+                    "phase": phase,
+                    "runs": bench.runs,
+                    "min_ms": ms(stats.min),
+                    "median_ms": ms(stats.median),
+                    "mean_ms": ms(stats.mean),
+                    "stddev_ms": ms(stats.stddev),
+                    "input": input_name,
+                }));
+            }
+        },
+    }
+}
+
+fn dump() {
+    let mut args = lapp::Args::new(DUMP_USAGE).start(2);
+    args.parse();
+    let puzzle = parse_day(&args);
+    let (_, input) = input_source(&args);
+
+    match input.map_err(PuzzleError::from).and_then(|input| puzzle.dump(input)) {
+        Ok(dump) => println!("{}", dump),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        },
+    }
+}
+
+fn generate() {
+    let mut args = lapp::Args::new(GENERATE_USAGE).start(2);
+    args.parse();
+    let puzzle = parse_day(&args);
+    let seed = u64::try_from(args.get_integer("seed"))
+        .unwrap_or_else(|_| args.quit("--seed cannot be negative"));
+    let size = match usize::try_from(args.get_integer("size")) {
+        Ok(0) => None,
+        Ok(size) => Some(size),
+        Err(_) => args.quit("--size cannot be negative"),
+    };
+
+    println!("{}", puzzle.generator.generate(seed, size));
+}
+
+fn run_crosscheck() {
+    let mut args = lapp::Args::new(CROSSCHECK_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let puzzles: Vec<&Puzzle> = match args.get_integer("day") {
+        0 => registry::all().filter(|puzzle| puzzle.algorithms().len() > 1).collect(),
+        _ => {
+            let puzzle = parse_day(&args);
+            if puzzle.algorithms().len() < 2 {
+                args.quit(&format!("day {} has only one algorithm", puzzle.day));
+            }
+            vec![puzzle]
+        },
+    };
+    let count = u64::try_from(args.get_integer("count"))
+        .ok()
+        .filter(|&count| count > 0)
+        .unwrap_or_else(|| args.quit("--count must be at least 1"));
+    let first_seed = u64::try_from(args.get_integer("seed"))
+        .unwrap_or_else(|_| args.quit("--seed cannot be negative"));
+    let size = match usize::try_from(args.get_integer("size")) {
+        Ok(0) => None,
+        Ok(size) => Some(size),
+        Err(_) => args.quit("--size cannot be negative"),
+    };
+    let timeout = parse_timeout(&args);
+
+    let mut disagreements = 0;
+    let mut uncompared = 0;
+    for puzzle in puzzles {
+        let day = puzzle.day;
+        let result = crosscheck::crosscheck(puzzle, first_seed..first_seed + count, size, timeout);
+        disagreements += result.disagreements.len();
+        if result.inputs == 0 {
+            uncompared += 1;
+        }
+
+        match format {
+            Format::Text => {
+                let algorithms = puzzle.algorithms().join(" and ");
+                let timed_out = match result.timed_out {
+                    0 => String::new(),
+                    count => format!(" ({} more timed out)", count),
+                };
+                if result.inputs == 0 {
+                    println!("Day {:>2}: {} compared no inputs{}", day, algorithms, timed_out);
+                    continue;
+                }
+                if result.disagreements.is_empty() {
+                    println!("Day {:>2}: {} agree on {} inputs{}", day, algorithms, result.inputs, timed_out);
+                    continue;
+                }
+                println!("Day {:>2}: {} disagree on {} of {} inputs{}", day, algorithms, result.disagreements.len(), result.inputs, timed_out);
+                for disagreement in &result.disagreements {
+                    let answers: Vec<String> = disagreement.answers.iter()
+                        .map(|(algorithm, answer)| match answer {
+                            Ok(answer) => format!("{}={}", algorithm, answer),
+                            Err(e) => format!("{} error: {}", algorithm, e),
+                        })
+                        .collect();
+                    println!("  seed {} part {}: {}", disagreement.seed, disagreement.part, answers.join(", "));
+                }
+            },
+            Format::Json => {
+                let records: Vec<serde_json::Value> = result.disagreements.iter()
+                    .map(|disagreement| {
+                        let answers: serde_json::Map<String, serde_json::Value> = disagreement.answers.iter()
+                            .map(|(algorithm, answer)| {
+                                let answer = match answer {
+                                    Ok(answer) => serde_json::Value::from(answer),
+                                    Err(e) => serde_json::json!({ "error": e }),
+                                };
+                                (algorithm.to_string(), answer)
+                            })
+                            .collect();
+                        serde_json::json!({ "seed": disagreement.seed, "part": disagreement.part, "answers": answers })
+                    })
+                    .collect();
+                println!("{}", serde_json::json!({
+                    "day": day,
+                    "algorithms": puzzle.algorithms(),
+                    "inputs": result.inputs,
+                    "timed_out": result.timed_out,
+                    "size": size.unwrap_or(puzzle.generator.default_size),
+                    "disagreements": records,
+                }));
+            },
+        }
+    }
+
+    if disagreements > 0 {
+        if format == Format::Text {
+            println!("Regenerate an input with `generate <day> --seed <seed>` to investigate");
+        }
+        process::exit(1);
+    }
+    if uncompared > 0 {
+        if format == Format::Text {
+            println!("Every input timed out on {} of the days, so try a smaller --size or a longer --timeout", uncompared);
+        }
+        process::exit(1);
+    }
+}
+
+fn repl() {
+    let mut args = lapp::Args::new(REPL_USAGE).start(2);
+    args.parse();
+    let puzzle = parse_day(&args);
+    let params = parse_params(&args, puzzle);
+    let timeout = parse_timeout(&args);
+    if args.get_string("file") == "-" && args.get_string("input").is_empty() {
+        args.quit("the repl reads commands from stdin, so give an input file or --input");
+    }
+    let (input_name, input) = input_source(&args);
+
+    let mut session = match input.map_err(PuzzleError::from).and_then(|input| Session::new(puzzle, input, params)) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        },
+    };
+    println!("Loaded day {} ({}) from {}. Type help for the commands.", puzzle.day, puzzle.title, input_name);
+
+    let mut stdin = io::stdin().lock();
+    loop {
+        print!("day {}> ", puzzle.day);
+        io::stdout().flush().expect("could not write the prompt");
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            // End of input, such as Ctrl-D
+            Ok(0) => {
+                println!();
+                break;
+            },
+            Ok(_) => (),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            },
+        }
+        if matches!(line.trim(), "quit" | "exit") {
+            break;
+        }
+
+        match cancel::with_timeout(timeout, || session.execute(&line)) {
+            Ok(output) if output.is_empty() => (),
+            Ok(output) => println!("{}", output),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+fn serve() {
+    let mut args = lapp::Args::new(SERVE_USAGE).start(2);
+    args.parse();
+    let port = u16::try_from(args.get_integer("port"))
+        .unwrap_or_else(|_| args.quit("--port must be between 0 and 65535"));
+    let timeout = parse_timeout(&args);
+    let address = format!("{}:{}", args.get_string("host"), port);
 
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Could not listen on {}: {}", address, e);
+            process::exit(2);
+        },
+    };
+    match listener.local_addr() {
+        Ok(address) => println!("Listening on http://{}", address),
+        Err(_) => println!("Listening on http://{}", address),
+    }
+
+    server::serve(listener, timeout);
+}
+
+fn write_report() {
+    let mut args = lapp::Args::new(REPORT_USAGE).start(2);
+    args.parse();
+    let output = args.get_string("output");
+    let format = match args.get_string("format").as_str() {
+        "" => report::Format::from_path(Path::new(&output))
+            .unwrap_or_else(|| args.quit("cannot tell the format from the output file, give --format markdown or html")),
+        name => report::Format::from_name(name)
+            .unwrap_or_else(|| args.quit(&format!("unknown report format '{}', expected markdown or html", name))),
+    };
+    let timeout = parse_timeout(&args);
+
+    let report = report::Report::collect(Path::new(&args.get_string("inputs")), timeout);
+    if let Err(e) = fs::write(&output, report.render(format)) {
+        eprintln!("Could not write the report to {}: {}", output, e);
+        process::exit(2);
+    }
+
+    let failed = report.days.iter().filter(|day| day.outcome.is_err()).count();
+    println!("Wrote a report of {} days to {} ({} failed, {} without input)",
+        report.days.len(), output, failed, report.skipped.len());
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn help_day() {
+    let mut args = lapp::Args::new(HELP_DAY_USAGE).start(2);
+    args.parse();
+    let puzzle = parse_day(&args);
+
+    println!("Day {}: {}", puzzle.day, puzzle.title);
+    let tags: Vec<&str> = puzzle.tags.iter().map(Tag::name).collect();
+    println!("Tags: {}", tags.join(", "));
+    println!("Examples: {}", puzzle.examples.len());
+    println!("Generated input size: {} (default {})", puzzle.generator.size, puzzle.generator.default_size);
+
+    let params = puzzle.params();
+    if params.iter().next().is_none() {
+        println!("Parameters: none");
+        return;
+    }
+    println!("Parameters:");
+    for (param, value) in params.iter() {
+        println!("  {}={} ({})", param.name, value, param.kind());
+        println!("      {}", param.help);
+    }
+}
+
+fn list() {
+    let mut args = lapp::Args::new(LIST_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let tag = match args.get_string("tag").as_str() {
+        "" => None,
+        name => match Tag::from_name(name) {
+            Some(tag) => Some(tag),
+            None => {
+                let names: Vec<&str> = Tag::ALL.iter().map(Tag::name).collect();
+                args.quit(&format!("unknown tag '{}', expected one of {}", name, names.join(", ")))
+            },
+        },
+    };
+
+    let puzzles: Vec<&Puzzle> = registry::all()
+        .filter(|puzzle| tag.is_none_or(|tag| puzzle.has_tag(tag)))
+        .collect();
+
+    match format {
+        Format::Text => {
+            let title_width = puzzles.iter().map(|puzzle| puzzle.title.len()).chain(["Title".len()]).max().unwrap();
+            println!("Day | {:<title_width$} | Examples | Tags", "Title");
+            println!("{}", "-".repeat(3 + 3 + title_width + 3 + 8 + 3 + 4));
+            for puzzle in puzzles {
+                let tags: Vec<&str> = puzzle.tags.iter().map(Tag::name).collect();
+                println!("{:>3} | {:<title_width$} | {:>8} | {}",
+                    puzzle.day, puzzle.title, puzzle.examples.len(), tags.join(", "));
+            }
+        },
+        Format::Json => {
+            for puzzle in puzzles {
+                let tags: Vec<&str> = puzzle.tags.iter().map(Tag::name).collect();
+                println!("{}", serde_json::json!({
+                    "day": puzzle.day,
+                    "title": puzzle.title,
+                    "tags": tags,
+                    "examples": puzzle.examples.len(),
+                }));
+            }
+        },
+    }
+}
+
+fn solve_all() {
+    let mut args = lapp::Args::new(ALL_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let part = parse_part(&args);
+
+    let start = Instant::now();
+    let results = solve_inputs(&args.get_string("inputs"), part, parse_timeout(&args), parse_jobs(&args));
+    match format {
+        Format::Text => {
+            print_table(&results);
+            println!("Elapsed: {}", format_duration(start.elapsed()));
+        },
+        Format::Json => {
+            for result in &results {
+                match &result.outcome {
+                    Outcome::Solved(run) => print_json(&run.to_json(result.day, &result.input)),
+                    Outcome::Skipped(note) => eprintln!("Day {}: {}", result.day, note),
+                    Outcome::Failed(e) => eprintln!("Day {}: error: {}", result.day, e),
+                }
+            }
+        },
+    }
+
+    let failed = |result: &DayResult| match &result.outcome {
+        Outcome::Solved(run) => run.error().is_some(),
+        Outcome::Skipped(_) => false,
+        Outcome::Failed(_) => true,
+    };
+    if results.iter().any(failed) {
+        process::exit(1);
+    }
+}
+
+fn verify() {
+    let mut args = lapp::Args::new(VERIFY_USAGE).start(2);
+    args.parse();
+    let format = parse_format(&args);
+    let part = parse_part(&args);
+
+    let answers_file = args.get_string("answers");
+    let expected = match ExpectedAnswers::load(&answers_file) {
+        Ok(expected) => expected,
+        Err(e) => {
+            eprintln!("Could not read answers from {}: {}", answers_file, e);
+            process::exit(2);
+        },
+    };
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut missing = 0;
+
+    for result in solve_inputs(&args.get_string("inputs"), part, parse_timeout(&args), parse_jobs(&args)) {
+        let day = result.day;
+        let run = match result.outcome {
+            Outcome::Solved(run) => run,
+            Outcome::Skipped(note) => {
+                match format {
+                    Format::Text => println!("Day {:>2}: {}", day, note),
+                    Format::Json => eprintln!("Day {}: {}", day, note),
+                }
+                continue;
+            },
+            Outcome::Failed(e) => {
+                // None of the requested parts has an answer to compare
+                failed += if part.is_some() { 1 } else { 2 };
+                match format {
+                    Format::Text => println!("Day {:>2}: FAIL error: {}", day, e),
+                    Format::Json => eprintln!("Day {}: error: {}", day, e),
+                }
+                continue;
+            },
+        };
+
+        let mut records = run.to_json(day, &result.input);
+        for (record, (part, part_run)) in records.iter_mut().zip(run.parts()) {
+            let answer = match part_run {
+                Ok(part_run) => &part_run.answer,
+                Err(e) => {
+                    failed += 1;
+                    match format {
+                        Format::Text => println!("Day {:>2} part {}: FAIL error: {}", day, part, e),
+                        Format::Json => {
+                            record["expected"] = expected.get(day, part).into();
+                            record["status"] = Verdict::Fail.to_string().into();
+                        },
+                    }
+                    continue;
+                },
+            };
+            let verdict = expected.check(day, part, answer);
+            match verdict {
+                Verdict::Pass => passed += 1,
+                Verdict::Fail => failed += 1,
+                Verdict::Missing => missing += 1,
+            }
+
+            match format {
+                Format::Text => match verdict {
+                    Verdict::Fail => println!("Day {:>2} part {}: {} (expected {}, got {})",
+                        day, part, verdict, expected.get(day, part).unwrap(), answer),
+                    _ => println!("Day {:>2} part {}: {} ({})", day, part, verdict, answer),
+                },
+                Format::Json => {
+                    record["expected"] = expected.get(day, part).into();
+                    record["status"] = verdict.to_string().into();
+                },
+            }
+        }
+        if format == Format::Json {
+            print_json(&records);
+        }
+    }
+
+    if format == Format::Text {
+        println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    }
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+/// Solves every day that has a dayNN.txt file in `dir`, up to `jobs` at once, noting
+/// the days that were skipped. Each day is given up on after `timeout`, if there is one.
+/// The results are in order of day, and each day's timings are measured on the
+/// thread that solved it.
+fn solve_inputs(dir: &str, part: Option<u8>, timeout: Option<Duration>, jobs: usize) -> Vec<DayResult> {
+    let puzzles: Vec<&Puzzle> = registry::all().collect();
+
+    pool::map(&puzzles, jobs, |puzzle| {
+        let day = puzzle.day;
+        let path = Path::new(dir).join(format!("day{:02}.txt", day));
+        let outcome = match File::open(&path) {
+            Ok(file) => match cancel::with_timeout(timeout, || puzzle.run(BufReader::new(file), part)) {
+                Ok(run) => Outcome::Solved(run),
+                Err(e) => Outcome::Failed(e),
+            },
+            Err(e) => Outcome::Skipped(format!("skipped: {} ({})", path.display(), e)),
+        };
+        DayResult { day, input: path.display().to_string(), outcome }
+    })
+}
+
+/// The puzzle for the `<day>` or `--day` argument.
+fn parse_day(args: &lapp::Args) -> &'static Puzzle {
     let d = args.get_integer("day");
+    match u32::try_from(d).ok().and_then(registry::get) {
+        Some(puzzle) => puzzle,
+        None => {
+            let days: Vec<String> = registry::all().map(|puzzle| puzzle.day.to_string()).collect();
+            args.quit(&format!("there is no day {}, expected one of {}", d, days.join(", ")))
+        },
+    }
+}
+
+/// The puzzle's parameters, with any overrides given with `--param` and `--algorithm`.
+fn parse_params(args: &lapp::Args, puzzle: &Puzzle) -> Params {
+    let mut params = puzzle.params();
+    for assignment in args.get_strings("param") {
+        if let Err(e) = params.assign(&assignment) {
+            args.quit(&e.to_string());
+        }
+    }
+
+    let algorithm = args.get_string("algorithm");
+    if !algorithm.is_empty() {
+        if puzzle.algorithms().is_empty() {
+            args.quit(&format!("day {} has only one algorithm", puzzle.day));
+        }
+        if let Err(e) = params.set(ALGORITHM, &algorithm) {
+            args.quit(&e.to_string());
+        }
+    }
+    params
+}
+
+/// The input chosen with the `<file>` and `--input` arguments, along with a name for it.
+fn input_source(args: &lapp::Args) -> (String, io::Result<Box<dyn BufRead>>) {
     let f = args.get_string("file");
-    println!("Solving day {} with input {}", d, f);
-
-    let input = read_lines(f);
-
-    match d {
-        1 => day01::solve(input),
-        2 => day02::solve(input),
-        3 => day03::solve(input),
-        4 => day04::solve(input),
-        5 => day05::solve(input),
-        6 => day06::solve(input),
-        7 => day07::solve(input),
-        8 => day08::solve(input),
-        9 => day09::solve(input),
-        10 => day10::solve(input),
-        11 => day11::solve(input),
-        12 => day12::solve(input),
-        13 => day13::solve(input),
-        14 => day14::solve(input),
-        15 => day15::solve(input),
-        16 => day16::solve(input),
-        17 => day17::solve(input),
-        18 => day18::solve(input),
-        19 => day19::solve(input),
-        20 => day20::solve(input),
-        21 => day21::solve(input),
-        22 => day22::solve(input),
-        23 => day23::solve(input),
-        24 => day24::solve(input),
-        25 => day25::solve(input),
-        i32::MIN..=0_i32 | 2_i32..=i32::MAX => println!("Invalid input!"),
-    }
-}
-
-
-fn read_lines(filename: String) -> io::Lines<BufReader<File>> {
+    let inline = args.get_string("input");
+    if f != "-" && !inline.is_empty() {
+        args.quit("give either an input file or --input, not both");
+    }
+
+    if !inline.is_empty() {
+        (String::from("<inline>"), Ok(Box::new(Cursor::new(inline))))
+    } else if f == "-" {
+        (String::from("<stdin>"), open_input(&f))
+    } else {
+        let input = open_input(&f);
+        (f, input)
+    }
+}
+
+/// The `--timeout` for each solve, or `None` for no limit.
+fn parse_timeout(args: &lapp::Args) -> Option<Duration> {
+    let seconds = args.get_float("timeout");
+    if seconds < 0.0 || !seconds.is_finite() {
+        args.quit("--timeout must be a number of seconds, or 0 for no limit");
+    }
+    Some(Duration::from_secs_f64(seconds as f64)).filter(|timeout| !timeout.is_zero())
+}
+
+/// How many days or inputs to solve at once, from `--jobs`.
+fn parse_jobs(args: &lapp::Args) -> usize {
+    match usize::try_from(args.get_integer("jobs")) {
+        Ok(0) => pool::default_jobs(),
+        Ok(jobs) => jobs,
+        Err(_) => args.quit("--jobs cannot be negative"),
+    }
+}
+
+fn parse_format(args: &lapp::Args) -> Format {
+    match args.get_string("format").as_str() {
+        "text" => Format::Text,
+        "json" => Format::Json,
+        other => args.quit(&format!("unknown format '{}', expected text or json", other)),
+    }
+}
+
+/// The part to solve, or `None` for both.
+fn parse_part(args: &lapp::Args) -> Option<u8> {
+    match args.get_string("part").as_str() {
+        "1" => Some(1),
+        "2" => Some(2),
+        "both" => None,
+        other => args.quit(&format!("unknown part '{}', expected 1, 2 or both", other)),
+    }
+}
+
+fn print_json(records: &[serde_json::Value]) {
+    for record in records {
+        println!("{}", record);
+    }
+}
+
+fn print_table(results: &[DayResult]) {
+    let answer_width = |part: fn(&Run) -> String, header: &str| {
+        results.iter()
+            .filter_map(|result| match &result.outcome {
+                Outcome::Solved(run) => Some(run),
+                _ => None,
+            })
+            .map(|run| part(run).len())
+            .chain([header.len()])
+            .max()
+            .unwrap()
+    };
+    let w1 = answer_width(|run| format_answer(&run.part1), "Part 1");
+    let w2 = answer_width(|run| format_answer(&run.part2), "Part 2");
+
+    println!("Day | {:<w1$} | {:<w2$} | {:>10} | {:>10} | {:>10}", "Part 1", "Part 2", "Parse", "Part 1", "Part 2");
+    println!("{}", "-".repeat(3 + 3 + w1 + 3 + w2 + 3 * (3 + 10)));
+
+    let mut total = Duration::ZERO;
+    for DayResult { day, outcome, .. } in results {
+        match outcome {
+            Outcome::Solved(run) => {
+                println!("{:>3} | {:<w1$} | {:<w2$} | {:>10} | {:>10} | {:>10}",
+                    day,
+                    format_answer(&run.part1),
+                    format_answer(&run.part2),
+                    format_duration(run.parse_time),
+                    format_part_duration(&run.part1),
+                    format_part_duration(&run.part2));
+                total += run.total_time();
+            },
+            Outcome::Skipped(note) => println!("{:>3} | {}", day, note),
+            Outcome::Failed(e) => {
+                // Indent the diagnostic's context lines past the day column
+                let message = e.to_string().replace('\n', "\n    ");
+                println!("{:>3} | error: {}", day, message);
+            },
+        }
+    }
+
+    println!("Total time: {}", format_duration(total));
+}
+
+fn print_batch_table(results: &[BatchResult]) {
+    let solved = || results.iter().filter_map(|result| result.outcome.as_ref().ok());
+    let file_width = results.iter()
+        .map(|result| result.path.display().to_string().len())
+        .chain(["File".len()])
+        .max()
+        .unwrap();
+    let w1 = solved().map(|run| format_answer(&run.part1).len()).chain(["Part 1".len()]).max().unwrap();
+    let w2 = solved().map(|run| format_answer(&run.part2).len()).chain(["Part 2".len()]).max().unwrap();
+
+    println!("{:<file_width$} | {:<w1$} | {:<w2$} | {:>10} | {:>10} | {:>10}", "File", "Part 1", "Part 2", "Parse", "Part 1", "Part 2");
+    println!("{}", "-".repeat(file_width + 3 + w1 + 3 + w2 + 3 * (3 + 10)));
+
+    for BatchResult { path, outcome } in results {
+        match outcome {
+            Ok(run) => println!("{:<file_width$} | {:<w1$} | {:<w2$} | {:>10} | {:>10} | {:>10}",
+                path.display(),
+                format_answer(&run.part1),
+                format_answer(&run.part2),
+                format_duration(run.parse_time),
+                format_part_duration(&run.part1),
+                format_part_duration(&run.part2)),
+            Err(e) => {
+                // Indent the diagnostic's context lines past the file column
+                let message = e.to_string().replace('\n', &format!("\n{}", " ".repeat(file_width + 1)));
+                println!("{:<file_width$} | error: {}", path.display(), message);
+            },
+        }
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+/// The answer to a part for the table, its error if it failed, or a dash if it
+/// was not asked for.
+fn format_answer(part_run: &Option<Result<PartRun, PuzzleError>>) -> String {
+    match part_run {
+        Some(Ok(part_run)) => part_run.answer.to_string(),
+        Some(Err(e)) => format!("error: {}", e),
+        None => String::from("-"),
+    }
+}
+
+fn format_part_duration(part_run: &Option<Result<PartRun, PuzzleError>>) -> String {
+    match part_run {
+        Some(Ok(part_run)) => format_duration(part_run.time),
+        _ => String::from("-"),
+    }
+}
+
+
+/// Opens the input file for reading, or stdin if the filename is `-`.
+fn open_input(filename: &str) -> io::Result<Box<dyn BufRead>> {
+    if filename == "-" {
+        return Ok(Box::new(io::stdin().lock()));
+    }
     // Open the file in read-only mode.
-    let file = File::open(filename).unwrap(); 
-    // Read the file line by line, and return an iterator of the lines of the file.
-    return io::BufReader::new(file).lines(); 
+    let file = File::open(filename)?;
+    Ok(Box::new(BufReader::new(file)))
 }
@@ -0,0 +1,280 @@
+use std::error::Error;
+use std::fmt;
+
+/// The value of a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Integer(u32),
+    Flag(bool),
+    /// One of a fixed list of names, such as which algorithm to use.
+    Choice { choice: &'static str, choices: &'static [&'static str] },
+}
+
+impl Value {
+    /// Parses `text` as a value of the same kind as `self`.
+    fn parse_like(&self, text: &str) -> Option<Value> {
+        match self {
+            Value::Integer(_) => text.parse().ok().map(Value::Integer),
+            Value::Flag(_) => match text {
+                "true" | "yes" | "on" => Some(Value::Flag(true)),
+                "false" | "no" | "off" => Some(Value::Flag(false)),
+                _ => None,
+            },
+            Value::Choice { choices, .. } => choices.iter()
+                .find(|&&choice| choice == text)
+                .map(|&choice| Value::Choice { choice, choices }),
+        }
+    }
+
+    /// What the value is, for help and error messages.
+    pub fn kind(&self) -> String {
+        match self {
+            Value::Integer(_) => String::from("a non-negative integer"),
+            Value::Flag(_) => String::from("true or false"),
+            Value::Choice { choices, .. } => format!("one of {}", choices.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Flag(b) => write!(f, "{}", b),
+            Value::Choice { choice, .. } => write!(f, "{}", choice),
+        }
+    }
+}
+
+/// A tuning constant of a day's puzzle, such as how long a race lasts.
+///
+/// Each day declares its parameters in [`Solution::PARAMS`](crate::Solution::PARAMS),
+/// with the values from the puzzle as defaults.
+#[derive(Debug)]
+pub struct Param {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub default: Value,
+    /// The smallest value an integer parameter accepts.
+    pub minimum: u32,
+}
+
+impl Param {
+    pub const fn integer(name: &'static str, help: &'static str, default: u32) -> Param {
+        Param { name, help, default: Value::Integer(default), minimum: 0 }
+    }
+
+    /// An integer parameter that cannot be 0, such as the player's hit points.
+    pub const fn positive(name: &'static str, help: &'static str, default: u32) -> Param {
+        Param { name, help, default: Value::Integer(default), minimum: 1 }
+    }
+
+    pub const fn flag(name: &'static str, help: &'static str, default: bool) -> Param {
+        Param { name, help, default: Value::Flag(default), minimum: 0 }
+    }
+
+    /// A parameter that takes one of `choices`, the first being the default.
+    pub const fn choice(name: &'static str, help: &'static str, choices: &'static [&'static str]) -> Param {
+        Param { name, help, default: Value::Choice { choice: choices[0], choices }, minimum: 0 }
+    }
+
+    /// A parameter named [`ALGORITHM`] that chooses between the ways a day can be
+    /// solved, the first being the default.
+    pub const fn algorithm(help: &'static str, algorithms: &'static [&'static str]) -> Param {
+        Param::choice(ALGORITHM, help, algorithms)
+    }
+
+    /// The names this parameter can take, or none if it is not a choice.
+    pub fn choices(&self) -> &'static [&'static str] {
+        match self.default {
+            Value::Choice { choices, .. } => choices,
+            _ => &[],
+        }
+    }
+
+    /// What values the parameter takes, for help and error messages.
+    pub fn kind(&self) -> String {
+        match self.default {
+            Value::Integer(_) if self.minimum == 1 => String::from("a positive integer"),
+            Value::Integer(_) if self.minimum > 1 => format!("an integer of at least {}", self.minimum),
+            other => other.kind(),
+        }
+    }
+
+    /// Parses `text` as a value of this parameter.
+    fn parse(&self, text: &str) -> Option<Value> {
+        self.default.parse_like(text)
+            .filter(|value| !matches!(value, Value::Integer(n) if *n < self.minimum))
+    }
+}
+
+/// The name of the parameter that selects a day's algorithm, when it has more than one.
+///
+/// A day with a brute-force solution alongside a faster one declares both, so that
+/// they can be checked against each other on generated inputs.
+pub const ALGORITHM: &str = "algorithm";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParamError {
+    /// The assignment was not of the form `name=value`.
+    Malformed(String),
+    Unknown { name: String, known: Vec<&'static str> },
+    Invalid { name: &'static str, value: String, expected: String },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Malformed(text) =>
+                write!(f, "expected a parameter like name=value, found '{}'", text),
+            ParamError::Unknown { name, known } if known.is_empty() =>
+                write!(f, "unknown parameter '{}', this day has no parameters", name),
+            ParamError::Unknown { name, known } =>
+                write!(f, "unknown parameter '{}', expected one of {}", name, known.join(", ")),
+            ParamError::Invalid { name, value, expected } =>
+                write!(f, "invalid value '{}' for parameter '{}', expected {}", value, name, expected),
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+/// The values of a day's parameters: the declared defaults, with any overrides.
+#[derive(Debug, Clone)]
+pub struct Params {
+    declared: &'static [Param],
+    values: Vec<Value>,
+}
+
+impl Params {
+    /// The defaults of the declared parameters.
+    pub fn new(declared: &'static [Param]) -> Self {
+        Params { declared, values: declared.iter().map(|param| param.default).collect() }
+    }
+
+    /// Overrides the parameter `name` with `value`, parsed as the kind of its default.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        let i = self.declared.iter().position(|param| param.name == name)
+            .ok_or_else(|| ParamError::Unknown {
+                name: name.to_string(),
+                known: self.declared.iter().map(|param| param.name).collect(),
+            })?;
+        let param = &self.declared[i];
+        self.values[i] = param.parse(value)
+            .ok_or_else(|| ParamError::Invalid { name: param.name, value: value.to_string(), expected: param.kind() })?;
+        Ok(())
+    }
+
+    /// Overrides a parameter from an assignment like `time-limit=1000`.
+    pub fn assign(&mut self, assignment: &str) -> Result<(), ParamError> {
+        let (name, value) = assignment.split_once('=')
+            .ok_or_else(|| ParamError::Malformed(assignment.to_string()))?;
+        self.set(name.trim(), value.trim())
+    }
+
+    /// The declared parameters, with their current values.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Param, Value)> + '_ {
+        self.declared.iter().zip(self.values.iter().copied())
+    }
+
+    fn get(&self, name: &str) -> Value {
+        self.iter()
+            .find(|(param, _)| param.name == name)
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("parameter '{}' is not declared", name))
+    }
+
+    /// The value of an integer parameter. Panics if there is no such parameter.
+    pub fn integer(&self, name: &str) -> u32 {
+        match self.get(name) {
+            Value::Integer(n) => n,
+            other => panic!("parameter '{}' is {}, not an integer", name, other.kind()),
+        }
+    }
+
+    /// The value of a flag parameter. Panics if there is no such parameter.
+    pub fn flag(&self, name: &str) -> bool {
+        match self.get(name) {
+            Value::Flag(b) => b,
+            other => panic!("parameter '{}' is {}, not a flag", name, other.kind()),
+        }
+    }
+
+    /// The value of a choice parameter. Panics if there is no such parameter.
+    pub fn choice(&self, name: &str) -> &'static str {
+        match self.get(name) {
+            Value::Choice { choice, .. } => choice,
+            other => panic!("parameter '{}' is {}, not a choice", name, other.kind()),
+        }
+    }
+
+    /// The chosen [`ALGORITHM`]. Panics if the day does not declare one.
+    pub fn algorithm(&self) -> &'static str {
+        self.choice(ALGORITHM)
+    }
+}
+
+impl Default for Params {
+    /// No parameters, for days that do not declare any.
+    fn default() -> Self {
+        Params::new(&[])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param::integer("steps", "How many steps to take", 100),
+        Param::positive("size", "How big the grid is", 10),
+        Param::flag("stuck", "Whether the corners are stuck", true),
+        Param::algorithm("How to step the grid", &["naive", "sparse"]),
+    ];
+
+    #[test]
+    fn test_defaults() {
+        let params = Params::new(PARAMS);
+        assert_eq!(100, params.integer("steps"));
+        assert!(params.flag("stuck"));
+        assert_eq!("naive", params.algorithm());
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut params = Params::new(PARAMS);
+        params.assign("steps=4").unwrap();
+        params.assign("stuck = false").unwrap();
+        params.assign("algorithm=sparse").unwrap();
+        assert_eq!(4, params.integer("steps"));
+        assert!(!params.flag("stuck"));
+        assert_eq!("sparse", params.algorithm());
+    }
+
+    #[test]
+    fn test_assign_errors() {
+        let mut params = Params::new(PARAMS);
+        assert_eq!(Err(ParamError::Malformed(String::from("steps"))), params.assign("steps"));
+        assert_eq!(
+            "unknown parameter 'speed', expected one of steps, size, stuck, algorithm",
+            params.assign("speed=3").unwrap_err().to_string());
+        assert_eq!(
+            "invalid value '-1' for parameter 'steps', expected a non-negative integer",
+            params.assign("steps=-1").unwrap_err().to_string());
+        assert_eq!(
+            "invalid value '0' for parameter 'size', expected a positive integer",
+            params.assign("size=0").unwrap_err().to_string());
+        assert!(params.assign("stuck=maybe").is_err());
+        assert_eq!(
+            "invalid value 'fast' for parameter 'algorithm', expected one of naive, sparse",
+            params.assign("algorithm=fast").unwrap_err().to_string());
+        assert_eq!(100, params.integer("steps"));
+    }
+
+    #[test]
+    #[should_panic(expected = "not declared")]
+    fn test_undeclared() {
+        Params::default().integer("steps");
+    }
+}
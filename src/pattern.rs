@@ -0,0 +1,267 @@
+use std::cmp::Reverse;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::error::{ self, PuzzleError, Result };
+
+/// The shape of a line of input, written like the line itself with a `{name}`
+/// for each field, such as `{name} can fly {speed} km/s for {time} seconds.`
+///
+/// A field runs up to the first place the text after it in the template appears,
+/// or to the end of the line if it is last, and is never empty. Two fields cannot
+/// be next to each other, as there would be no telling where one ends.
+#[derive(Debug, Clone, Copy)]
+pub struct Pattern {
+    template: &'static str,
+}
+
+/// A piece of a template: text the line must contain, or a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece {
+    Literal(&'static str),
+    Field(&'static str),
+}
+
+impl Pattern {
+    pub const fn new(template: &'static str) -> Pattern {
+        Pattern { template }
+    }
+
+    /// The text every matching line starts with, before the first field.
+    pub fn prefix(&self) -> &'static str {
+        match self.pieces().first() {
+            Some(Piece::Literal(literal)) => literal,
+            _ => "",
+        }
+    }
+
+    /// Matches the whole of `line`, the `line_number`th line of the input. If it
+    /// does not match, the error points at the first character that is not what the
+    /// template expects.
+    pub fn parse<'a>(&self, line_number: usize, line: &'a str) -> Result<Fields<'a>> {
+        self.parse_within(line_number, line, 0..line.len())
+    }
+
+    /// Matches the part of `line` in the byte range `within`, so that errors point
+    /// into the full line.
+    fn parse_within<'a>(&self, line_number: usize, line: &'a str, within: Range<usize>) -> Result<Fields<'a>> {
+        let end = within.end;
+        let mut pos = within.start;
+        let mut values = Vec::new();
+
+        let pieces = self.pieces();
+        for (i, piece) in pieces.iter().enumerate() {
+            match *piece {
+                Piece::Literal(literal) => {
+                    if !line[pos..end].starts_with(literal) {
+                        let mismatch = pos + common_prefix(&line[pos..end], literal);
+                        return Err(expected(line_number, line, mismatch, end, literal));
+                    }
+                    pos += literal.len();
+                },
+                Piece::Field(name) => {
+                    let Some(first) = line[pos..end].chars().next() else {
+                        return Err(PuzzleError::invalid_token(line_number, line, &line[end..end], format!("expected {}", name)));
+                    };
+                    let field_end = match pieces.get(i + 1) {
+                        Some(Piece::Literal(literal)) => {
+                            // Start looking after the first character, so that the field is never empty
+                            let from = pos + first.len_utf8();
+                            match line[from..end].find(literal) {
+                                Some(offset) => from + offset,
+                                None => {
+                                    let mismatch = closest_match(line, from..end, literal);
+                                    return Err(expected(line_number, line, mismatch, end, literal));
+                                },
+                            }
+                        },
+                        _ => end,
+                    };
+                    values.push((name, &line[pos..field_end]));
+                    pos = field_end;
+                },
+            }
+        }
+
+        if pos < end {
+            let rest = &line[pos..end];
+            return Err(PuzzleError::invalid_token(line_number, line, rest, format!("unexpected '{}' at the end", rest)));
+        }
+        Ok(Fields { line_number, line, values })
+    }
+
+    /// Splits the template into literals and fields.
+    ///
+    /// # Panics
+    ///
+    /// If a brace is not closed, or two fields are next to each other.
+    fn pieces(&self) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut rest = self.template;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let close = rest.find('}').unwrap_or_else(|| panic!("unclosed '{{' in pattern '{}'", self.template));
+                    assert!(!matches!(pieces.last(), Some(Piece::Field(_))), "two fields in a row in pattern '{}'", self.template);
+                    pieces.push(Piece::Field(&rest[1..close]));
+                    rest = &rest[close + 1..];
+                },
+                Some(open) => {
+                    pieces.push(Piece::Literal(&rest[..open]));
+                    rest = &rest[open..];
+                },
+                None => {
+                    pieces.push(Piece::Literal(rest));
+                    rest = "";
+                },
+            }
+        }
+        pieces
+    }
+}
+
+/// The fields of a line that matched a [`Pattern`], each a slice of the line.
+#[derive(Debug, Clone)]
+pub struct Fields<'a> {
+    line_number: usize,
+    line: &'a str,
+    values: Vec<(&'static str, &'a str)>,
+}
+
+impl<'a> Fields<'a> {
+    /// The text of the field called `name`.
+    ///
+    /// # Panics
+    ///
+    /// If the pattern has no such field.
+    pub fn str(&self, name: &str) -> &'a str {
+        self.values.iter()
+            .find(|(field, _)| *field == name)
+            .map(|&(_, value)| value)
+            .unwrap_or_else(|| panic!("the pattern has no field '{}'", name))
+    }
+
+    /// The field called `name`, parsed as a number.
+    pub fn number<T: FromStr>(&self, name: &str) -> Result<T> {
+        error::parse_number(self.line_number, self.line, self.str(name))
+    }
+
+    /// The value paired with whichever of `choices` the field called `name` is.
+    pub fn choice<T: Copy>(&self, name: &str, choices: &[(&str, T)]) -> Result<T> {
+        let value = self.str(name);
+        choices.iter()
+            .find(|(choice, _)| *choice == value)
+            .map(|&(_, result)| result)
+            .ok_or_else(|| {
+                let names: Vec<String> = choices.iter().map(|(choice, _)| format!("'{}'", choice)).collect();
+                let expected = match names.split_last() {
+                    Some((last, [])) => last.clone(),
+                    Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+                    None => String::from("nothing"),
+                };
+                self.invalid(name, format!("expected {}", expected))
+            })
+    }
+
+    /// The field called `name` split at each `separator`, with every item matched
+    /// against `item`, such as the `cars: 9, akitas: 3` in one of day 16's aunts.
+    pub fn list(&self, name: &str, separator: &str, item: &Pattern) -> Result<Vec<Fields<'a>>> {
+        let line_start = self.line.as_ptr() as usize;
+        self.str(name).split(separator)
+            .map(|text| {
+                let start = text.as_ptr() as usize - line_start;
+                item.parse_within(self.line_number, self.line, start..start + text.len())
+            })
+            .collect()
+    }
+
+    /// A parse error pointing at the field called `name`, for values that have the
+    /// right form but are out of range.
+    pub fn invalid(&self, name: &str, message: impl Into<String>) -> PuzzleError {
+        PuzzleError::invalid_token(self.line_number, self.line, self.str(name), message)
+    }
+}
+
+/// The length in bytes of the longest common prefix of `text` and `literal`.
+fn common_prefix(text: &str, literal: &str) -> usize {
+    text.char_indices()
+        .zip(literal.chars())
+        .find(|&((_, a), b)| a != b)
+        .map_or_else(|| text.len().min(literal.len()), |((i, _), _)| i)
+}
+
+/// Where in the byte range `within` of `line` the text stops matching `literal`,
+/// at the place that matches the most of it, or the end if none of it appears.
+fn closest_match(line: &str, within: Range<usize>, literal: &str) -> usize {
+    line[within.clone()].char_indices()
+        .map(|(i, _)| within.start + i)
+        .map(|start| (common_prefix(&line[start..within.end], literal), start))
+        .filter(|&(matched, _)| matched > 0)
+        // The earliest of the longest
+        .max_by_key(|&(matched, start)| (matched, Reverse(start)))
+        .map_or(within.end, |(matched, start)| start + matched)
+}
+
+/// An error for a line missing `literal`, pointing at the byte offset `at`.
+fn expected(line_number: usize, line: &str, at: usize, end: usize, literal: &str) -> PuzzleError {
+    let found = line[at..end].chars().next().map(|c| &line[at..at + c.len_utf8()]).unwrap_or(&line[end..end]);
+    PuzzleError::invalid_token(line_number, line, found, format!("expected '{}'", literal.trim()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REINDEER: Pattern = Pattern::new("{name} can fly {speed} km/s for {time} seconds.");
+
+    /// The column and message of a parse error.
+    fn error_at<T: std::fmt::Debug>(result: Result<T>) -> (usize, String) {
+        match result {
+            Err(PuzzleError::Parse { column, message, .. }) => (column, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fields() {
+        let fields = REINDEER.parse(1, "Comet can fly 14 km/s for 10 seconds.").unwrap();
+        assert_eq!("Comet", fields.str("name"));
+        assert_eq!(14, fields.number::<u32>("speed").unwrap());
+        assert_eq!(10, fields.number::<u32>("time").unwrap());
+        assert_eq!("", REINDEER.prefix());
+        assert_eq!("turn on ", Pattern::new("turn on {x},{y}").prefix());
+
+        // Fields run to the first match of what follows them
+        let fields = Pattern::new("{a} to {b}").parse(1, "a to b to c").unwrap();
+        assert_eq!(("a", "b to c"), (fields.str("a"), fields.str("b")));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!((21, String::from("expected 'km/s for'")), error_at(REINDEER.parse(1, "Comet can fly 14 km/h for 10 seconds.")));
+        assert_eq!((37, String::from("expected 'seconds.'")), error_at(REINDEER.parse(1, "Comet can fly 14 km/s for 10 seconds")));
+        assert_eq!((1, String::from("expected name")), error_at(REINDEER.parse(1, "")));
+        assert_eq!((38, String::from("unexpected '!' at the end")), error_at(REINDEER.parse(1, "Comet can fly 14 km/s for 10 seconds.!")));
+
+        let fields = REINDEER.parse(1, "Comet can fly fast km/s for 10 seconds.").unwrap();
+        assert_eq!(15, error_at(fields.number::<u32>("speed")).0);
+    }
+
+    #[test]
+    fn test_choice_and_list() {
+        let fields = Pattern::new("{who} would {change} {units}").parse(1, "Alice would lose 54").unwrap();
+        assert_eq!(-1, fields.choice("change", &[("gain", 1), ("lose", -1)]).unwrap());
+        let fields = Pattern::new("{who} would {change} {units}").parse(1, "Alice would win 54").unwrap();
+        let (column, message) = error_at(fields.choice("change", &[("gain", 1), ("lose", -1)]));
+        assert_eq!((13, "expected 'gain' or 'lose'"), (column, message.as_str()));
+
+        let compound = Pattern::new("{compound}: {count}");
+        let fields = Pattern::new("Sue {number}: {compounds}").parse(1, "Sue 1: cars: 9, akitas: 3").unwrap();
+        let compounds = fields.list("compounds", ", ", &compound).unwrap();
+        assert_eq!(vec![("cars", "9"), ("akitas", "3")], compounds.iter().map(|item| (item.str("compound"), item.str("count"))).collect::<Vec<_>>());
+
+        let fields = Pattern::new("Sue {number}: {compounds}").parse(1, "Sue 1: cars: 9, akitas 3").unwrap();
+        assert_eq!(25, error_at(fields.list("compounds", ", ", &compound)).0);
+    }
+}
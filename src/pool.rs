@@ -0,0 +1,70 @@
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::thread;
+
+/// How many jobs to run at once when no limit is given: one for each core.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Calls `f` with each of `items`, on up to `jobs` threads at once, and returns
+/// the results in the same order as `items` whichever finishes first.
+///
+/// The items are handed out in order as threads become free, so putting the
+/// slowest first shortens the total time. With one job everything runs on the
+/// current thread.
+pub fn map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let jobs = jobs.clamp(1, items.len().max(1));
+    if jobs == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let results: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else { break };
+                *results[i].lock().unwrap() = Some(f(item));
+            });
+        }
+    });
+
+    results.into_iter()
+        .map(|result| result.into_inner().unwrap().expect("every item was handed out"))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_results_are_in_order() {
+        let items: Vec<u64> = (0..20).collect();
+        // Make the early items finish last
+        let squares = map(&items, 4, |&n| {
+            thread::sleep(Duration::from_millis(20 - n));
+            n * n
+        });
+        assert_eq!(items.iter().map(|n| n * n).collect::<Vec<u64>>(), squares);
+    }
+
+    #[test]
+    fn test_jobs_limit() {
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        map(&[(); 12], 3, |_| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            most.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+        assert!(most.load(Ordering::SeqCst) <= 3);
+        assert!(map(&Vec::<u8>::new(), 0, |&n| n).is_empty());
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt;
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+use crate::*;
+use crate::error::Result;
+use crate::examples::Example;
+use crate::generate::Generator;
+use crate::params::{ Param, Params, ALGORITHM };
+use crate::runner::{ self, PartRun, Run };
+use crate::solution::{ Query, QueryError, Visual };
+
+/// What kind of puzzle a day is, for finding days that exercise similar code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    BruteForce,
+    Combinatorics,
+    Graph,
+    Grid,
+    Hashing,
+    Json,
+    Math,
+    Optimization,
+    Search,
+    Simulation,
+    Strings,
+}
+
+impl Tag {
+    pub const ALL: &'static [Tag] = &[
+        Tag::BruteForce,
+        Tag::Combinatorics,
+        Tag::Graph,
+        Tag::Grid,
+        Tag::Hashing,
+        Tag::Json,
+        Tag::Math,
+        Tag::Optimization,
+        Tag::Search,
+        Tag::Simulation,
+        Tag::Strings,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tag::BruteForce => "brute-force",
+            Tag::Combinatorics => "combinatorics",
+            Tag::Graph => "graph",
+            Tag::Grid => "grid",
+            Tag::Hashing => "hashing",
+            Tag::Json => "json",
+            Tag::Math => "math",
+            Tag::Optimization => "optimization",
+            Tag::Search => "search",
+            Tag::Simulation => "simulation",
+            Tag::Strings => "strings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Tag> {
+        Tag::ALL.iter().copied().find(|tag| tag.name() == name)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A [`Solution`] with its input type erased, so that every day can live in one table.
+pub trait DynSolution: Sync {
+    fn params(&self) -> &'static [Param];
+
+    fn run(&self, input: &mut dyn BufRead, part: Option<u8>, params: &Params) -> Result<Run>;
+
+    fn visualize(&self, input: &mut dyn BufRead, params: &Params) -> Result<Vec<Visual>>;
+
+    fn dump(&self, input: &mut dyn BufRead) -> Result<String>;
+
+    fn load(&self, input: &mut dyn BufRead) -> Result<Box<dyn Loaded>>;
+
+    fn queries(&self) -> &'static [Query];
+}
+
+/// A puzzle input that has been parsed, with its type erased, so that it can be
+/// solved and queried repeatedly without parsing it again.
+pub trait Loaded {
+    fn solve(&self, part: u8, params: &Params) -> Result<PartRun>;
+
+    fn dump(&self) -> String;
+
+    fn visualize(&self, params: &Params) -> Result<Vec<Visual>>;
+
+    fn query(&self, name: &str, args: &[&str], params: &Params) -> std::result::Result<String, QueryError>;
+}
+
+/// The input of the [`Solution`] `S`, adapted to [`Loaded`].
+struct Parsed<S: Solution>(S::Input);
+
+impl<S: Solution> Loaded for Parsed<S> {
+    fn solve(&self, part: u8, params: &Params) -> Result<PartRun> {
+        runner::solve::<S>(&self.0, part, params)
+    }
+
+    fn dump(&self) -> String {
+        format!("{:#?}", self.0)
+    }
+
+    fn visualize(&self, params: &Params) -> Result<Vec<Visual>> {
+        S::visualize(&self.0, params)
+    }
+
+    fn query(&self, name: &str, args: &[&str], params: &Params) -> std::result::Result<String, QueryError> {
+        S::query(&self.0, params, name, args)
+    }
+}
+
+/// Adapts the [`Solution`] `S` to [`DynSolution`].
+pub struct Solver<S>(PhantomData<fn() -> S>);
+
+impl<S> Solver<S> {
+    pub const NEW: Self = Solver(PhantomData);
+}
+
+impl<S: Solution + 'static> DynSolution for Solver<S> {
+    fn params(&self) -> &'static [Param] {
+        S::PARAMS
+    }
+
+    fn run(&self, input: &mut dyn BufRead, part: Option<u8>, params: &Params) -> Result<Run> {
+        runner::run::<S>(input, part, params)
+    }
+
+    fn visualize(&self, input: &mut dyn BufRead, params: &Params) -> Result<Vec<Visual>> {
+        S::visualize(&runner::parse::<S>(input)?, params)
+    }
+
+    fn dump(&self, input: &mut dyn BufRead) -> Result<String> {
+        Ok(format!("{:#?}", runner::parse::<S>(input)?))
+    }
+
+    fn load(&self, input: &mut dyn BufRead) -> Result<Box<dyn Loaded>> {
+        Ok(Box::new(Parsed::<S>(runner::parse::<S>(input)?)))
+    }
+
+    fn queries(&self) -> &'static [Query] {
+        S::QUERIES
+    }
+}
+
+/// A day's puzzle: its number, title and tags, along with its solver, examples
+/// and input generator.
+/// Each `dayNN` module declares one of these as `PUZZLE`.
+pub struct Puzzle {
+    pub day: u32,
+    pub title: &'static str,
+    pub tags: &'static [Tag],
+    pub examples: &'static [Example],
+    pub generator: Generator,
+    pub solver: &'static dyn DynSolution,
+}
+
+impl Puzzle {
+    /// The default values of the puzzle's parameters, ready to be overridden.
+    pub fn params(&self) -> Params {
+        Params::new(self.solver.params())
+    }
+
+    /// Parses the input and solves the given part, or both parts if `part` is `None`,
+    /// with the default parameters.
+    pub fn run(&self, input: impl BufRead, part: Option<u8>) -> Result<Run> {
+        self.run_with(input, part, &self.params())
+    }
+
+    /// Like [`run`](Self::run), but with the given parameters, which should come
+    /// from [`params`](Self::params).
+    pub fn run_with(&self, mut input: impl BufRead, part: Option<u8>, params: &Params) -> Result<Run> {
+        self.solver.run(&mut input, part, params)
+    }
+
+    /// Parses the input and draws [`Visual`]s of the solution, if the puzzle has any.
+    pub fn visualize(&self, mut input: impl BufRead, params: &Params) -> Result<Vec<Visual>> {
+        self.solver.visualize(&mut input, params)
+    }
+
+    /// Parses the input, without solving it, and shows what it was parsed into.
+    pub fn dump(&self, mut input: impl BufRead) -> Result<String> {
+        self.solver.dump(&mut input)
+    }
+
+    /// Parses the input once, to be solved or queried as many times as needed.
+    pub fn load(&self, mut input: impl BufRead) -> Result<Box<dyn Loaded>> {
+        self.solver.load(&mut input)
+    }
+
+    /// The questions the REPL can ask about the puzzle's inputs.
+    pub fn queries(&self) -> &'static [Query] {
+        self.solver.queries()
+    }
+
+    /// The names of the puzzle's algorithms, the default first, or none if it can
+    /// only be solved one way.
+    pub fn algorithms(&self) -> &'static [&'static str] {
+        self.solver.params().iter()
+            .find(|param| param.name == ALGORITHM)
+            .map_or(&[], Param::choices)
+    }
+
+    pub fn has_tag(&self, tag: Tag) -> bool {
+        self.tags.contains(&tag)
+    }
+}
+
+static PUZZLES: [&Puzzle; 25] = [
+    &day01::PUZZLE,
+    &day02::PUZZLE,
+    &day03::PUZZLE,
+    &day04::PUZZLE,
+    &day05::PUZZLE,
+    &day06::PUZZLE,
+    &day07::PUZZLE,
+    &day08::PUZZLE,
+    &day09::PUZZLE,
+    &day10::PUZZLE,
+    &day11::PUZZLE,
+    &day12::PUZZLE,
+    &day13::PUZZLE,
+    &day14::PUZZLE,
+    &day15::PUZZLE,
+    &day16::PUZZLE,
+    &day17::PUZZLE,
+    &day18::PUZZLE,
+    &day19::PUZZLE,
+    &day20::PUZZLE,
+    &day21::PUZZLE,
+    &day22::PUZZLE,
+    &day23::PUZZLE,
+    &day24::PUZZLE,
+    &day25::PUZZLE,
+];
+
+/// Every registered puzzle, in order of day.
+pub fn all() -> impl Iterator<Item = &'static Puzzle> {
+    PUZZLES.iter().copied()
+}
+
+/// The puzzle for the given day, if there is one.
+pub fn get(day: u32) -> Option<&'static Puzzle> {
+    all().find(|puzzle| puzzle.day == day)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_are_registered_in_order() {
+        let days: Vec<u32> = all().map(|puzzle| puzzle.day).collect();
+        assert_eq!((1..=25).collect::<Vec<u32>>(), days);
+    }
+
+    #[test]
+    fn test_get() {
+        assert_eq!("Not Quite Lisp", get(1).unwrap().title);
+        assert!(get(0).is_none());
+        assert!(get(26).is_none());
+    }
+
+    #[test]
+    fn test_algorithms() {
+        assert_eq!(&["permutations", "held-karp"], get(9).unwrap().algorithms());
+        assert!(get(1).unwrap().algorithms().is_empty());
+    }
+
+    #[test]
+    fn test_dump() {
+        let dump = get(21).unwrap().dump("Hit Points: 104\nDamage: 8\nArmor: 1".as_bytes()).unwrap();
+        assert!(dump.starts_with("Boss {\n"), "{}", dump);
+        assert!(dump.contains("104"), "{}", dump);
+        assert!(get(21).unwrap().dump("Hit Points: lots".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_tag_names() {
+        for &tag in Tag::ALL {
+            assert_eq!(Some(tag), Tag::from_name(tag.name()));
+        }
+        assert_eq!(None, Tag::from_name("unknown"));
+    }
+}
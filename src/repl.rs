@@ -0,0 +1,135 @@
+use std::io::BufRead;
+
+use crate::error::PuzzleError;
+use crate::params::Params;
+use crate::registry::{ Loaded, Puzzle };
+use crate::solution::QueryError;
+
+/// The commands every day understands, with their arguments and help.
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("part1", "", "Solve part 1"),
+    ("part2", "", "Solve part 2"),
+    ("set", "<name>=<value>", "Change one of the day's parameters"),
+    ("params", "", "List the day's parameters and their values"),
+    ("show", "", "Print what the input was parsed into"),
+    ("draw", "", "Draw pictures of the solution, for the days that have them"),
+    ("help", "", "List the commands"),
+    ("quit", "", "Leave the REPL"),
+];
+
+/// A puzzle input that has been parsed once, along with the parameters to solve
+/// it with, for answering commands one line at a time.
+pub struct Session {
+    puzzle: &'static Puzzle,
+    loaded: Box<dyn Loaded>,
+    params: Params,
+}
+
+impl Session {
+    /// Parses the input, which fails if it is not valid for the puzzle.
+    pub fn new(puzzle: &'static Puzzle, input: impl BufRead, params: Params) -> Result<Self, PuzzleError> {
+        let loaded = puzzle.load(input)?;
+        Ok(Session { puzzle, loaded, params })
+    }
+
+    pub fn puzzle(&self) -> &'static Puzzle {
+        self.puzzle
+    }
+
+    /// Runs one command line and returns what to print. Blank lines print nothing.
+    /// Leaving the REPL is up to the caller.
+    pub fn execute(&mut self, line: &str) -> Result<String, QueryError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = words.split_first() else {
+            return Ok(String::new());
+        };
+
+        match command {
+            "part1" | "part2" => {
+                let part = if command == "part1" { 1 } else { 2 };
+                let run = self.loaded.solve(part, &self.params)?;
+                Ok(format!("Part {}: {} ({:.3}ms)", part, run.answer, run.time.as_secs_f64() * 1000.0))
+            },
+            "set" => {
+                let assignment = args.join(" ");
+                self.params.assign(&assignment)?;
+                let (name, _) = assignment.split_once('=').expect("the assignment was parsed");
+                Ok(self.describe_params(Some(name.trim())))
+            },
+            "params" => Ok(self.describe_params(None)),
+            "show" => Ok(self.loaded.dump()),
+            "draw" => {
+                let visuals = self.loaded.visualize(&self.params)?;
+                if visuals.is_empty() {
+                    return Ok(format!("Day {} has nothing to draw", self.puzzle.day));
+                }
+                let drawings: Vec<String> = visuals.iter()
+                    .map(|visual| format!("{}\n{}", visual.title, visual.text))
+                    .collect();
+                Ok(drawings.join("\n\n"))
+            },
+            "help" => Ok(self.help()),
+            _ if self.puzzle.queries().iter().any(|query| query.name == command) =>
+                self.loaded.query(command, args, &self.params),
+            _ => Err(QueryError::UnknownCommand(command.to_string())),
+        }
+    }
+
+    /// The commands, including the day's own queries.
+    pub fn help(&self) -> String {
+        let queries = self.puzzle.queries().iter().map(|query| (query.name, query.args, query.help));
+        let commands: Vec<(String, &str)> = COMMANDS.iter().copied()
+            .chain(queries)
+            .map(|(name, args, help)| (format!("{} {}", name, args).trim_end().to_string(), help))
+            .collect();
+        let width = commands.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+        commands.iter()
+            .map(|(usage, help)| format!("  {:<width$}  {}", usage, help))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// The parameters with their current values, or only the one called `only`.
+    fn describe_params(&self, only: Option<&str>) -> String {
+        let lines: Vec<String> = self.params.iter()
+            .filter(|(param, _)| only.is_none_or(|name| param.name == name))
+            .map(|(param, value)| format!("{} = {}", param.name, value))
+            .collect();
+        if lines.is_empty() {
+            return format!("Day {} has no parameters", self.puzzle.day);
+        }
+        lines.join("\n")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    fn session(day: u32, input: &str) -> Session {
+        let puzzle = registry::get(day).unwrap();
+        Session::new(puzzle, input.as_bytes(), puzzle.params()).unwrap()
+    }
+
+    #[test]
+    fn test_solve_and_set() {
+        let mut session = session(14, "Comet can fly 14 km/s for 10 seconds, but then must rest for 127 seconds.\n\
+            Dancer can fly 16 km/s for 11 seconds, but then must rest for 162 seconds.");
+        assert!(session.execute("part1").unwrap().starts_with("Part 1: 2660 ("));
+        assert_eq!("time-limit = 1000", session.execute("set time-limit=1000").unwrap());
+        assert!(session.execute("part1").unwrap().starts_with("Part 1: 1120 ("));
+        assert!(matches!(session.execute("set speed=3"), Err(QueryError::Param(_))));
+        assert_eq!("", session.execute("   ").unwrap());
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut session = session(1, "(()(()(");
+        assert_eq!("Floor 1 after 3 instructions", session.execute("floor-at 3").unwrap());
+        assert!(matches!(session.execute("floor-at"), Err(QueryError::Usage(_))));
+        assert!(matches!(session.execute("wire a"), Err(QueryError::UnknownCommand(_))));
+        assert!(session.execute("help").unwrap().contains("floor-at <instructions>"));
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cancel;
+use crate::error::PuzzleError;
+use crate::registry::{ self, Puzzle };
+use crate::runner::{ PartRun, Run };
+use crate::solution::Visual;
+
+/// The kind of file a [`Report`] is written as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "markdown" | "md" => Some(Format::Markdown),
+            "html" => Some(Format::Html),
+            _ => None,
+        }
+    }
+
+    /// The format that matches the extension of `path`, if there is one.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "md" | "markdown" => Some(Format::Markdown),
+            "html" | "htm" => Some(Format::Html),
+            _ => None,
+        }
+    }
+}
+
+/// How one day went in a full run.
+#[derive(Debug)]
+pub struct DayReport {
+    pub day: u32,
+    pub title: &'static str,
+    /// Where the input came from.
+    pub input: String,
+    /// The algorithm the day was solved with, if it has more than one.
+    pub algorithm: Option<&'static str>,
+    /// The answers, or the error that stopped the day from being solved.
+    pub outcome: Result<Run, String>,
+    pub visuals: Vec<Visual>,
+}
+
+impl DayReport {
+    /// Solves the puzzle with its default parameters and draws its visuals, giving
+    /// up on each after `timeout` if there is one.
+    pub fn solve(puzzle: &Puzzle, input_name: &str, input: &str, timeout: Option<Duration>) -> Self {
+        let params = puzzle.params();
+        let outcome = cancel::with_timeout(timeout, || puzzle.run_with(input.as_bytes(), None, &params))
+            .map_err(|e| e.to_string());
+        // A day that could not be solved has nothing to draw
+        let visuals = match outcome {
+            Ok(_) => cancel::with_timeout(timeout, || puzzle.visualize(input.as_bytes(), &params)).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+
+        DayReport {
+            day: puzzle.day,
+            title: puzzle.title,
+            input: input_name.to_string(),
+            algorithm: puzzle.algorithms().first().copied(),
+            outcome,
+            visuals,
+        }
+    }
+
+    /// The error that stopped the day from being solved, or else the error from
+    /// each part that failed.
+    fn errors(&self) -> Vec<String> {
+        match &self.outcome {
+            Ok(run) => run.parts()
+                .filter_map(|(part, run)| run.as_ref().err().map(|e| format!("part {}: {}", part, e)))
+                .collect(),
+            Err(e) => vec![e.clone()],
+        }
+    }
+}
+
+/// The answers, timings and visuals from solving every day, ready to be written out.
+#[derive(Debug)]
+pub struct Report {
+    pub days: Vec<DayReport>,
+    /// The days that had no input file.
+    pub skipped: Vec<u32>,
+}
+
+impl Report {
+    /// Solves every day that has a dayNN.txt file in `dir`.
+    pub fn collect(dir: &Path, timeout: Option<Duration>) -> Self {
+        let mut days = Vec::new();
+        let mut skipped = Vec::new();
+        for puzzle in registry::all() {
+            let path = dir.join(format!("day{:02}.txt", puzzle.day));
+            match fs::read_to_string(&path) {
+                Ok(input) => days.push(DayReport::solve(puzzle, &path.display().to_string(), &input, timeout)),
+                Err(_) => skipped.push(puzzle.day),
+            }
+        }
+        Report { days, skipped }
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Markdown => self.to_markdown(),
+            Format::Html => self.to_html(),
+        }
+    }
+
+    fn total_time(&self) -> Duration {
+        self.days.iter()
+            .filter_map(|day| day.outcome.as_ref().ok())
+            .map(Run::total_time)
+            .sum()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Advent of Code 2015\n\n");
+        out += "| Day | Title | Part 1 | Part 2 | Algorithm | Parse | Part 1 time | Part 2 time |\n";
+        out += "|---:|---|---|---|---|---:|---:|---:|\n";
+        for day in &self.days {
+            let cells = summary_cells(day);
+            let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            out += &format!("| {} |\n", cells.join(" | "));
+        }
+        out += &format!("\nTotal time: {}\n", format_duration(self.total_time()));
+        if !self.skipped.is_empty() {
+            out += &format!("\nDays without an input file: {}\n", join_days(&self.skipped));
+        }
+
+        for day in &self.days {
+            let errors = day.errors();
+            if errors.is_empty() && day.visuals.is_empty() {
+                continue;
+            }
+            out += &format!("\n## Day {}: {}\n\nInput: `{}`\n", day.day, day.title, day.input);
+            for e in &errors {
+                out += &format!("\n```text\nerror: {}\n```\n", e);
+            }
+            for visual in &day.visuals {
+                out += &format!("\n### {}\n\n```text\n{}\n```\n", visual.title, visual.text);
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(concat!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+            "<title>Advent of Code 2015</title>\n<style>\n",
+            "body { font-family: sans-serif; margin: 2em; }\n",
+            "table { border-collapse: collapse; }\n",
+            "th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }\n",
+            "td.number { text-align: right; }\n",
+            "pre { background: #f6f6f6; padding: 0.5em; line-height: 1; overflow-x: auto; }\n",
+            ".error { color: #b00; }\n",
+            "</style>\n</head>\n<body>\n<h1>Advent of Code 2015</h1>\n<table>\n",
+            "<tr><th>Day</th><th>Title</th><th>Part 1</th><th>Part 2</th><th>Algorithm</th>",
+            "<th>Parse</th><th>Part 1 time</th><th>Part 2 time</th></tr>\n",
+        ));
+        for day in &self.days {
+            let cells = summary_cells(day);
+            out += "<tr>";
+            for (i, cell) in cells.iter().enumerate() {
+                // The day and the timings line up on the right
+                let class = if i == 0 || i >= 5 { " class=\"number\"" } else { "" };
+                out += &format!("<td{}>{}</td>", class, escape_html(cell));
+            }
+            out += "</tr>\n";
+        }
+        out += "</table>\n";
+        out += &format!("<p>Total time: {}</p>\n", format_duration(self.total_time()));
+        if !self.skipped.is_empty() {
+            out += &format!("<p>Days without an input file: {}</p>\n", join_days(&self.skipped));
+        }
+
+        for day in &self.days {
+            let errors = day.errors();
+            if errors.is_empty() && day.visuals.is_empty() {
+                continue;
+            }
+            out += &format!("<h2>Day {}: {}</h2>\n<p>Input: <code>{}</code></p>\n",
+                day.day, escape_html(day.title), escape_html(&day.input));
+            for e in &errors {
+                out += &format!("<pre class=\"error\">error: {}</pre>\n", escape_html(e));
+            }
+            for visual in &day.visuals {
+                out += &format!("<h3>{}</h3>\n<pre>{}</pre>\n", escape_html(&visual.title), escape_html(&visual.text));
+            }
+        }
+        out += "</body>\n</html>\n";
+        out
+    }
+}
+
+/// The cells of the day's row in the summary table.
+fn summary_cells(day: &DayReport) -> Vec<String> {
+    let mut cells = vec![day.day.to_string(), day.title.to_string()];
+    match &day.outcome {
+        Ok(run) => {
+            cells.push(format_answer(&run.part1));
+            cells.push(format_answer(&run.part2));
+            cells.push(day.algorithm.unwrap_or("-").to_string());
+            cells.push(format_duration(run.parse_time));
+            cells.push(format_part_duration(&run.part1));
+            cells.push(format_part_duration(&run.part2));
+        },
+        Err(_) => {
+            cells.push(String::from("error"));
+            cells.push(String::from("error"));
+            cells.push(day.algorithm.unwrap_or("-").to_string());
+            cells.extend(["-", "-", "-"].map(String::from));
+        },
+    }
+    cells
+}
+
+fn join_days(days: &[u32]) -> String {
+    days.iter().map(u32::to_string).collect::<Vec<String>>().join(", ")
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+fn format_answer(part_run: &Option<Result<PartRun, PuzzleError>>) -> String {
+    match part_run {
+        Some(Ok(part_run)) => part_run.answer.to_string(),
+        Some(Err(_)) => String::from("error"),
+        None => String::from("-"),
+    }
+}
+
+fn format_part_duration(part_run: &Option<Result<PartRun, PuzzleError>>) -> String {
+    match part_run {
+        Some(Ok(part_run)) => format_duration(part_run.time),
+        _ => String::from("-"),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let day09 = registry::get(9).unwrap();
+        let day02 = registry::get(2).unwrap();
+        Report {
+            days: vec![
+                DayReport::solve(day02, "bad.txt", "1x1xfoo", None),
+                DayReport::solve(day09, "<example>", "London to Dublin = 464\nLondon to Belfast = 518\nDublin to Belfast = 141", None),
+            ],
+            skipped: vec![4, 5],
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Some(Format::Html), Format::from_path(Path::new("out/report.html")));
+        assert_eq!(Some(Format::Markdown), Format::from_path(Path::new("report.md")));
+        assert_eq!(None, Format::from_path(Path::new("report")));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = report().to_markdown();
+        assert!(markdown.contains("| 9 | All in a Single Night | 605 | 982 | permutations |"), "{}", markdown);
+        assert!(markdown.contains("| 2 | I Was Told There Would Be No Math | error | error | - |"), "{}", markdown);
+        assert!(markdown.contains("Days without an input file: 4, 5"));
+        assert!(markdown.contains("### Shortest route: "));
+        assert!(markdown.contains("Total: 605"));
+        assert!(markdown.contains("error: parse error on line 1, column 5"));
+    }
+
+    #[test]
+    fn test_html() {
+        let html = report().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<td>605</td>"));
+        assert!(html.contains("<h3>Longest route: "));
+        assert!(html.contains("-&gt;"));
+        assert!(html.ends_with("</html>\n"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::io::{ BufRead, Cursor };
+use std::time::{ Duration, Instant };
+
+use serde_json::{ json, Value };
+
+use crate::error::{ PuzzleError, Result };
+use crate::params::Params;
+use crate::solution::{ Answer, Solution };
+
+/// The answer to one part of a puzzle, along with how long it took.
+#[derive(Debug, Clone)]
+pub struct PartRun {
+    pub answer: Answer,
+    pub time: Duration,
+}
+
+/// The result of each part of a puzzle that was asked for, along with how long each phase took.
+#[derive(Debug)]
+pub struct Run {
+    pub parse_time: Duration,
+    pub part1: Option<Result<PartRun>>,
+    pub part2: Option<Result<PartRun>>,
+}
+
+impl Run {
+    /// The parts that were asked for, along with their part numbers.
+    pub fn parts(&self) -> impl Iterator<Item = (u8, &Result<PartRun>)> {
+        [(1, &self.part1), (2, &self.part2)]
+            .into_iter()
+            .filter_map(|(part, run)| run.as_ref().map(|run| (part, run)))
+    }
+
+    /// The parts that were solved, along with their part numbers.
+    pub fn solved(&self) -> impl Iterator<Item = (u8, &PartRun)> {
+        self.parts().filter_map(|(part, run)| run.as_ref().ok().map(|run| (part, run)))
+    }
+
+    /// The error from the first part that failed, if any did.
+    pub fn error(&self) -> Option<&PuzzleError> {
+        self.parts().find_map(|(_, run)| run.as_ref().err())
+    }
+
+    /// This run if every part was solved, or else the error from the first part that failed.
+    pub fn into_result(self) -> Result<Run> {
+        match self {
+            Run { part1: Some(Err(e)), .. } | Run { part2: Some(Err(e)), .. } => Err(e),
+            run => Ok(run),
+        }
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.solved().map(|(_, run)| run.time).sum::<Duration>()
+    }
+
+    /// One JSON record per part, for consumption by other tools. Parts that failed
+    /// have an `error` instead of an answer.
+    pub fn to_json(&self, day: u32, input: &str) -> Vec<Value> {
+        self.parts()
+            .map(|(part, run)| match run {
+                Ok(run) => json!({
+                    "day": day,
+                    "part": part,
+                    "answer": Value::from(&run.answer),
+                    "duration_ms": run.time.as_secs_f64() * 1000.0,
+                    "input": input,
+                }),
+                Err(e) => json!({
+                    "day": day,
+                    "part": part,
+                    "error": e.to_string(),
+                    "input": input,
+                }),
+            })
+            .collect()
+    }
+}
+
+/// Normalizes and parses the input, then solves the given part, or both parts if
+/// `part` is `None`, timing each phase separately. Fails if the input cannot be read
+/// or parsed, but otherwise keeps the result of each part, so that part 1's answer
+/// is not lost when part 2 fails.
+///
+/// `params` should hold the parameters declared in `S::PARAMS`.
+pub fn run<S: Solution>(input: impl BufRead, part: Option<u8>, params: &Params) -> Result<Run> {
+    let start = Instant::now();
+    let parsed = parse::<S>(input)?;
+    let parse_time = start.elapsed();
+
+    let part1 = match part {
+        None | Some(1) => Some(solve::<S>(&parsed, 1, params)),
+        _ => None,
+    };
+    let part2 = match part {
+        None | Some(2) => Some(solve::<S>(&parsed, 2, params)),
+        _ => None,
+    };
+
+    Ok(Run { parse_time, part1, part2 })
+}
+
+/// Solves part 1 or part 2 of an input that has already been parsed, timing it.
+pub fn solve<S: Solution>(input: &S::Input, part: u8, params: &Params) -> Result<PartRun> {
+    let part_fn = match part {
+        1 => S::part1,
+        2 => S::part2,
+        _ => panic!("there is no part {}", part),
+    };
+    let start = Instant::now();
+    let answer = part_fn(input, params)?;
+    Ok(PartRun { answer, time: start.elapsed() })
+}
+
+/// Normalizes and parses the input, as [`run`] does before solving it.
+pub fn parse<S: Solution>(mut input: impl BufRead) -> Result<S::Input> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let text = S::NORMALIZE.apply(&text);
+    S::parse(Cursor::new(text).lines())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Lines;
+
+    use super::*;
+    use crate::{ day01, day11 };
+    use crate::input::Normalize;
+
+    /// Answers part 1 with the first line of the input exactly as it was parsed, and
+    /// fails part 2.
+    struct FirstLine;
+
+    impl Solution for FirstLine {
+        type Input = String;
+
+        const NORMALIZE: Normalize = Normalize::NONE;
+
+        fn parse<R: BufRead>(mut input: Lines<R>) -> Result<String> {
+            Ok(input.next().transpose()?.unwrap_or_default())
+        }
+
+        fn part1(line: &String, _params: &Params) -> Result<Answer> {
+            Ok(line.as_str().into())
+        }
+
+        fn part2(_: &String, _params: &Params) -> Result<Answer> {
+            Err(PuzzleError::unsolvable("there is no part 2"))
+        }
+    }
+
+    #[test]
+    fn test_run_from_string() {
+        let run = run::<day01::Day01>(Cursor::new("(()))("), None, &Params::default()).unwrap();
+        assert_eq!(Answer::Number(0), run.part1.unwrap().unwrap().answer);
+        assert_eq!(Answer::Number(5), run.part2.unwrap().unwrap().answer);
+    }
+
+    #[test]
+    fn test_run_single_part() {
+        let run = run::<day01::Day01>(Cursor::new("(()))("), Some(2), &Params::default()).unwrap();
+        assert!(run.part1.is_none());
+        assert_eq!(vec![2], run.parts().map(|(part, _)| part).collect::<Vec<_>>());
+        assert_eq!(Answer::Number(5), run.part2.unwrap().unwrap().answer);
+    }
+
+    #[test]
+    fn test_run_keeps_each_part() {
+        let run = run::<FirstLine>(Cursor::new(""), None, &Params::default()).unwrap();
+        assert_eq!(Answer::from(""), run.part1.as_ref().unwrap().as_ref().unwrap().answer);
+        assert!(matches!(run.error(), Some(PuzzleError::Unsolvable(_))));
+        assert_eq!(vec![1], run.solved().map(|(part, _)| part).collect::<Vec<_>>());
+        assert!(run.into_result().is_err());
+    }
+
+    #[test]
+    fn test_run_normalizes_input() {
+        let run = run::<day11::Day11>(Cursor::new("\u{feff}abcdefgh \r\n\r\n"), Some(1), &Params::default()).unwrap();
+        assert_eq!(Answer::from("abcdffaa"), run.part1.unwrap().unwrap().answer);
+    }
+
+    #[test]
+    fn test_run_without_normalizing() {
+        let run = run::<FirstLine>(Cursor::new("\u{feff}line \r\n"), Some(1), &Params::default()).unwrap();
+        assert_eq!(Answer::from("\u{feff}line "), run.part1.unwrap().unwrap().answer);
+    }
+}
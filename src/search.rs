@@ -0,0 +1,233 @@
+use std::cmp::Reverse;
+use std::collections::{ BinaryHeap, HashMap, HashSet, VecDeque };
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::cancel;
+use crate::error::Result;
+
+/// A state in a search, such as a molecule partway through being built or a
+/// battle after some turns.
+pub trait State: Clone + Eq + Hash {
+    /// What a step costs, such as the mana spent on a spell. `Default` must be zero.
+    type Cost: Copy + Ord + Default + Add<Output = Self::Cost>;
+
+    /// The states one step away from this one, with the cost of each step.
+    fn neighbors(&self) -> Vec<(Self, Self::Cost)>;
+
+    fn is_goal(&self) -> bool;
+
+    /// An estimate of the cost still to reach a goal, for [`astar`] and [`greedy`].
+    /// For `astar` to find the cheapest path it must never overestimate it.
+    fn heuristic(&self) -> Self::Cost {
+        Self::Cost::default()
+    }
+}
+
+/// The way to a goal that a search found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    /// Every state along the way, from the start to the goal.
+    pub states: Vec<S>,
+}
+
+impl<S, C> Path<S, C> {
+    /// How many steps the path takes.
+    pub fn steps(&self) -> usize {
+        self.states.len() - 1
+    }
+}
+
+/// The path to a goal with the fewest steps, ignoring their costs, or `None` if no
+/// goal can be reached.
+pub fn bfs<S: State>(start: S) -> Result<Option<Path<S, usize>>> {
+    let mut nodes: Vec<(S, Option<usize>)> = vec![(start.clone(), None)];
+    let mut seen: HashSet<S> = HashSet::from([start]);
+    let mut queue: VecDeque<usize> = VecDeque::from([0]);
+
+    while let Some(i) = queue.pop_front() {
+        cancel::check()?;
+        if nodes[i].0.is_goal() {
+            let states = trace(&nodes, i);
+            return Ok(Some(Path { cost: states.len() - 1, states }));
+        }
+        for (next, _) in nodes[i].0.neighbors() {
+            if seen.insert(next.clone()) {
+                nodes.push((next, Some(i)));
+                queue.push_back(nodes.len() - 1);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The cheapest path to a goal, or `None` if no goal can be reached.
+pub fn dijkstra<S: State>(start: S) -> Result<Option<Path<S, S::Cost>>> {
+    best_first(start, |_, cost| cost, false)
+}
+
+/// The cheapest path to a goal, like [`dijkstra`], but trying the states that the
+/// [`heuristic`](State::heuristic) says are closest to a goal first.
+pub fn astar<S: State>(start: S) -> Result<Option<Path<S, S::Cost>>> {
+    best_first(start, |state, cost| cost + state.heuristic(), false)
+}
+
+/// The first path to a goal found by always trying the state with the lowest
+/// [`heuristic`](State::heuristic) next. It is usually much faster than [`astar`],
+/// but the path is only the cheapest one if every path to a goal costs the same.
+pub fn greedy<S: State>(start: S) -> Result<Option<Path<S, S::Cost>>> {
+    best_first(start, |state, _| state.heuristic(), true)
+}
+
+/// Searches from `start`, always expanding the state with the lowest `priority`,
+/// and the most recently found of those. A goal is accepted as soon as it is found
+/// if `stop_at_first`, or else only once it is expanded, which makes the path the
+/// cheapest one when the priority never overestimates the final cost.
+fn best_first<S: State, P: Ord>(
+    start: S,
+    priority: impl Fn(&S, S::Cost) -> P,
+    stop_at_first: bool,
+) -> Result<Option<Path<S, S::Cost>>> {
+    let zero = S::Cost::default();
+    // Every state found, with the index of the state it was reached from and the cost of getting there
+    let mut nodes: Vec<(S, Option<usize>)> = vec![(start.clone(), None)];
+    let mut costs: Vec<S::Cost> = vec![zero];
+    let mut cheapest: HashMap<S, S::Cost> = HashMap::from([(start.clone(), zero)]);
+    let mut queue = BinaryHeap::from([Reverse((priority(&start, zero), Reverse(0)))]);
+
+    let path = |nodes: &[(S, Option<usize>)], cost: S::Cost, i: usize| Some(Path { cost, states: trace(nodes, i) });
+
+    if stop_at_first && start.is_goal() {
+        return Ok(path(&nodes, zero, 0));
+    }
+
+    while let Some(Reverse((_, Reverse(i)))) = queue.pop() {
+        cancel::check()?;
+        let cost = costs[i];
+        if cheapest[&nodes[i].0] < cost {
+            // A cheaper way to this state was found after this one was queued
+            continue;
+        }
+        if nodes[i].0.is_goal() {
+            return Ok(path(&nodes, cost, i));
+        }
+
+        for (next, step) in nodes[i].0.neighbors() {
+            let next_cost = cost + step;
+            if cheapest.get(&next).is_some_and(|&known| known <= next_cost) {
+                continue;
+            }
+            cheapest.insert(next.clone(), next_cost);
+
+            let j = nodes.len();
+            let next_priority = priority(&next, next_cost);
+            let is_goal = stop_at_first && next.is_goal();
+            nodes.push((next, Some(i)));
+            costs.push(next_cost);
+            if is_goal {
+                return Ok(path(&nodes, next_cost, j));
+            }
+            queue.push(Reverse((next_priority, Reverse(j))));
+        }
+    }
+    Ok(None)
+}
+
+/// The states from the start to the `i`th node, following the links back.
+fn trace<S: Clone>(nodes: &[(S, Option<usize>)], i: usize) -> Vec<S> {
+    let mut states = Vec::new();
+    let mut current = Some(i);
+    while let Some(i) = current {
+        states.push(nodes[i].0.clone());
+        current = nodes[i].1;
+    }
+    states.reverse();
+    states
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A position on a number line, which can step forward by one for a cost of 3
+    /// or jump forward by four for a cost of 10, trying to reach `goal`.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Position {
+        at: u32,
+        goal: u32,
+    }
+
+    impl State for Position {
+        type Cost = u32;
+
+        fn neighbors(&self) -> Vec<(Self, u32)> {
+            [(1, 3), (4, 10)].into_iter()
+                .filter(|&(step, _)| self.at + step <= self.goal)
+                .map(|(step, cost)| (Position { at: self.at + step, goal: self.goal }, cost))
+                .collect()
+        }
+
+        fn is_goal(&self) -> bool {
+            self.at == self.goal
+        }
+
+        fn heuristic(&self) -> u32 {
+            // Jumping is the cheapest way forward, at 2.5 a step
+            (self.goal - self.at) * 5 / 2
+        }
+    }
+
+    fn positions(path: &Path<Position, impl Copy>) -> Vec<u32> {
+        path.states.iter().map(|state| state.at).collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(Position { at: 0, goal: 9 }).unwrap().unwrap();
+        assert_eq!(3, path.cost);
+        assert_eq!(3, path.steps());
+        assert_eq!((Some(&0), Some(&9)), (positions(&path).first(), positions(&path).last()));
+    }
+
+    #[test]
+    fn test_cheapest_path() {
+        // Three steps of 1 cost 9, less than one jump
+        for search in [dijkstra, astar] {
+            let path = search(Position { at: 0, goal: 11 }).unwrap().unwrap();
+            assert_eq!(29, path.cost);
+            assert_eq!(5, path.steps());
+            assert_eq!(Some(&11), positions(&path).last());
+        }
+        assert_eq!(0, dijkstra(Position { at: 5, goal: 5 }).unwrap().unwrap().cost);
+    }
+
+    #[test]
+    fn test_greedy() {
+        let path = greedy(Position { at: 0, goal: 8 }).unwrap().unwrap();
+        assert_eq!(vec![0, 4, 8], positions(&path));
+        assert_eq!(20, path.cost);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        struct Stuck;
+
+        impl State for Stuck {
+            type Cost = u32;
+
+            fn neighbors(&self) -> Vec<(Self, u32)> {
+                vec![(Stuck, 1)]
+            }
+
+            fn is_goal(&self) -> bool {
+                false
+            }
+        }
+
+        assert!(bfs(Stuck).unwrap().is_none());
+        assert!(dijkstra(Stuck).unwrap().is_none());
+    }
+}
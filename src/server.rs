@@ -0,0 +1,395 @@
+use std::io::{ self, BufRead, BufReader, Write };
+use std::net::{ TcpListener, TcpStream };
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{ json, Value };
+
+use crate::cancel;
+use crate::error::PuzzleError;
+use crate::registry::{ self, Puzzle };
+
+/// The largest request body that is accepted, which is far more than any puzzle input.
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+/// The longest request line or header that is accepted, counting its line ending.
+const MAX_LINE: usize = 8 * 1024;
+
+/// How long to wait before accepting again after a connection could not be accepted,
+/// so that running out of file descriptors does not turn into a busy loop.
+const ACCEPT_RETRY: Duration = Duration::from_millis(100);
+
+/// An HTTP request, with only the parts the API uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// The decoded query parameters, in order.
+    pub query: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Reads a request from an HTTP/1.1 client. Malformed requests are answered
+    /// with the returned error response.
+    pub fn read_from(reader: &mut impl BufRead) -> Result<Request, Response> {
+        let bad_request = |message: &str| Response::error(400, message);
+
+        let line = match read_line(reader) {
+            Ok(Some(line)) => line,
+            Ok(None) => return Err(Response::error(414, "the request line is too long")),
+            Err(_) => return Err(bad_request("could not read the request")),
+        };
+        let mut words = line.split_whitespace();
+        let (Some(method), Some(target), Some(_version)) = (words.next(), words.next(), words.next()) else {
+            return Err(bad_request("expected a request line like 'POST /days/1 HTTP/1.1'"));
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let mut content_length = 0;
+        loop {
+            let header = match read_line(reader) {
+                Ok(Some(header)) => header,
+                Ok(None) => return Err(Response::error(431, "a header is too long")),
+                Err(_) => return Err(bad_request("could not read the headers")),
+            };
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().map_err(|_| bad_request("invalid Content-Length"))?;
+                }
+            }
+        }
+        if content_length > MAX_BODY {
+            return Err(Response::error(413, &format!("the body must be at most {} bytes", MAX_BODY)));
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).map_err(|_| bad_request("the body is shorter than its Content-Length"))?;
+        let body = String::from_utf8(body).map_err(|_| bad_request("the body must be UTF-8 text"))?;
+
+        let query = query.split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(name), percent_decode(value))
+            })
+            .collect();
+
+        Ok(Request { method: method.to_string(), path: path.to_string(), query, body })
+    }
+}
+
+/// Reads one line, or `None` if it is longer than [`MAX_LINE`], so that a client
+/// cannot make the server hold an endless line in memory.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    io::Read::take(&mut *reader, MAX_LINE as u64).read_line(&mut line)?;
+    if line.len() == MAX_LINE && !line.ends_with('\n') {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+/// Decodes `%XX` escapes and `+` for spaces in part of a query string.
+fn percent_decode(text: &str) -> String {
+    let hex = |byte: &u8| (*byte as char).to_digit(16);
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match (bytes.get(i+1).and_then(hex), bytes.get(i+2).and_then(hex)) {
+                (Some(high), Some(low)) => {
+                    decoded.push((high * 16 + low) as u8);
+                    i += 2;
+                },
+                // Not an escape, so keep it as it is
+                _ => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A JSON response to a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    pub fn ok(body: Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    /// A response with the given status and a body like `{"error": "..."}`.
+    pub fn error(status: u16, message: &str) -> Self {
+        Response { status, body: json!({ "error": message }) }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Content Too Large",
+            414 => "URI Too Long",
+            422 => "Unprocessable Content",
+            431 => "Request Header Fields Too Large",
+            504 => "Gateway Timeout",
+            _ => "Internal Server Error",
+        }
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let body = self.body.to_string();
+        write!(writer, "HTTP/1.1 {} {}\r\n", self.status, self.reason())?;
+        write!(writer, "Content-Type: application/json\r\n")?;
+        write!(writer, "Content-Length: {}\r\n", body.len())?;
+        write!(writer, "Connection: close\r\n\r\n")?;
+        writer.write_all(body.as_bytes())?;
+        writer.flush()
+    }
+}
+
+/// Answers one request to the API:
+///
+/// - `GET /days` lists every puzzle, with its parameters and algorithms.
+/// - `GET /days/{n}` describes one puzzle.
+/// - `POST /days/{n}` solves the puzzle with the body as its input. The query can
+///   choose a `part` (1 or 2), and set any of the day's parameters by name,
+///   like `/days/14?part=1&time-limit=1000`.
+///
+/// Each solve is given up on after `timeout`, if there is one.
+pub fn handle(request: &Request, timeout: Option<Duration>) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => Response::ok(registry::all().map(describe).collect()),
+        ("GET", ["days", day]) => match find_puzzle(day) {
+            Ok(puzzle) => Response::ok(describe(puzzle)),
+            Err(response) => response,
+        },
+        ("POST", ["days", day]) => match find_puzzle(day) {
+            Ok(puzzle) => solve(puzzle, request, timeout),
+            Err(response) => response,
+        },
+        (_, ["days"]) | (_, ["days", _]) => Response::error(405, "use GET to describe puzzles, or POST to solve one"),
+        _ => Response::error(404, "unknown path, expected /days or /days/{n}"),
+    }
+}
+
+fn find_puzzle(day: &str) -> Result<&'static Puzzle, Response> {
+    day.parse().ok()
+        .and_then(registry::get)
+        .ok_or_else(|| Response::error(404, &format!("there is no day {}", day)))
+}
+
+fn describe(puzzle: &Puzzle) -> Value {
+    let tags: Vec<&str> = puzzle.tags.iter().map(|tag| tag.name()).collect();
+    let params: Vec<Value> = puzzle.params().iter()
+        .map(|(param, value)| json!({
+            "name": param.name,
+            "default": value.to_string(),
+            "kind": param.kind(),
+            "help": param.help,
+        }))
+        .collect();
+    json!({
+        "day": puzzle.day,
+        "title": puzzle.title,
+        "tags": tags,
+        "examples": puzzle.examples.len(),
+        "params": params,
+        "algorithms": puzzle.algorithms(),
+    })
+}
+
+fn solve(puzzle: &Puzzle, request: &Request, timeout: Option<Duration>) -> Response {
+    let mut part = None;
+    let mut params = puzzle.params();
+    for (name, value) in &request.query {
+        match name.as_str() {
+            "part" => match value.as_str() {
+                "1" => part = Some(1),
+                "2" => part = Some(2),
+                "both" => part = None,
+                _ => return Response::error(400, &format!("unknown part '{}', expected 1, 2 or both", value)),
+            },
+            _ => if let Err(e) = params.set(name, value) {
+                return Response::error(400, &e.to_string());
+            },
+        }
+    }
+
+    let result = cancel::with_timeout(timeout, || puzzle.run_with(request.body.as_bytes(), part, &params))
+        // Only fail the whole request if no part has an answer to show
+        .and_then(|run| match run.solved().count() {
+            0 => run.into_result(),
+            _ => Ok(run),
+        });
+    let run = match result {
+        Ok(run) => run,
+        Err(e) => {
+            let status = match e {
+                PuzzleError::TimedOut(_) | PuzzleError::Cancelled => 504,
+                _ => 422,
+            };
+            return Response::error(status, &e.to_string());
+        },
+    };
+
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let parts: Vec<Value> = run.parts()
+        .map(|(part, part_run)| match part_run {
+            Ok(part_run) => json!({
+                "part": part,
+                "answer": Value::from(&part_run.answer),
+                "duration_ms": ms(part_run.time),
+            }),
+            Err(e) => json!({ "part": part, "error": e.to_string() }),
+        })
+        .collect();
+    Response::ok(json!({
+        "day": puzzle.day,
+        "parse_ms": ms(run.parse_time),
+        "total_ms": ms(run.total_time()),
+        "parts": parts,
+    }))
+}
+
+/// Answers every connection to `listener`, each on its own thread, forever. A
+/// connection that cannot be accepted is logged and skipped.
+pub fn serve(listener: TcpListener, timeout: Option<Duration>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("error: could not accept a connection: {}", e);
+                thread::sleep(ACCEPT_RETRY);
+                continue;
+            },
+        };
+        thread::spawn(move || {
+            // The client may already have gone, and there is no one else to tell
+            let _ = answer(stream, timeout);
+        });
+    }
+}
+
+fn answer(stream: TcpStream, timeout: Option<Duration>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match Request::read_from(&mut reader) {
+        Ok(request) => handle(&request, timeout),
+        Err(response) => response,
+    };
+    let mut stream = stream;
+    response.write_to(&mut stream)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn request(method: &str, target: &str, body: &str) -> Request {
+        let raw = format!("{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, target, body.len(), body);
+        Request::read_from(&mut raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let request = request("POST", "/days/14?part=1&time-limit=1000&note=a%20b+c", "input");
+        assert_eq!("POST", request.method);
+        assert_eq!("/days/14", request.path);
+        assert_eq!(
+            vec![
+                (String::from("part"), String::from("1")),
+                (String::from("time-limit"), String::from("1000")),
+                (String::from("note"), String::from("a b c")),
+            ],
+            request.query);
+        assert_eq!("input", request.body);
+    }
+
+    #[test]
+    fn test_list_days() {
+        let response = handle(&request("GET", "/days", ""), None);
+        assert_eq!(200, response.status);
+        assert_eq!(25, response.body.as_array().unwrap().len());
+        assert_eq!("Not Quite Lisp", response.body[0]["title"]);
+        assert_eq!("held-karp", response.body[8]["algorithms"][1]);
+    }
+
+    #[test]
+    fn test_solve() {
+        let response = handle(&request("POST", "/days/1", "(()))("), None);
+        assert_eq!(200, response.status);
+        assert_eq!(json!(0), response.body["parts"][0]["answer"]);
+        assert_eq!(json!(5), response.body["parts"][1]["answer"]);
+
+        let response = handle(&request("POST", "/days/1?part=2", "(()))("), None);
+        assert_eq!(1, response.body["parts"].as_array().unwrap().len());
+        assert_eq!(json!(2), response.body["parts"][0]["part"]);
+    }
+
+    #[test]
+    fn test_solve_with_params() {
+        let input = "Comet can fly 14 km/s for 10 seconds, but then must rest for 127 seconds.\n\
+                     Dancer can fly 16 km/s for 11 seconds, but then must rest for 162 seconds.";
+        let response = handle(&request("POST", "/days/14?part=1&time-limit=1000", input), None);
+        assert_eq!(json!(1120), response.body["parts"][0]["answer"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(404, handle(&request("GET", "/days/26", ""), None).status);
+        assert_eq!(404, handle(&request("GET", "/nowhere", ""), None).status);
+        assert_eq!(405, handle(&request("DELETE", "/days/1", ""), None).status);
+        assert_eq!(400, handle(&request("POST", "/days/1?part=3", "("), None).status);
+        assert_eq!(400, handle(&request("POST", "/days/1?speed=3", "("), None).status);
+        assert_eq!(422, handle(&request("POST", "/days/2", "1x1xfoo"), None).status);
+
+        let response = handle(&request("POST", "/days/23", "jmp +0"), Some(Duration::from_millis(10)));
+        assert_eq!(504, response.status);
+    }
+
+    #[test]
+    fn test_long_lines() {
+        let raw = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(414, Request::read_from(&mut raw.as_bytes()).unwrap_err().status);
+
+        let raw = format!("GET /days HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(431, Request::read_from(&mut raw.as_bytes()).unwrap_err().status);
+
+        let raw = format!("GET /days HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_LINE - 100));
+        assert_eq!("/days", Request::read_from(&mut raw.as_bytes()).unwrap().path);
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, None));
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(b"POST /days/1?part=1 HTTP/1.1\r\nContent-Length: 6\r\n\r\n(()))(").unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json!(0), body["parts"][0]["answer"]);
+    }
+}
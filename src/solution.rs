@@ -0,0 +1,205 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{ BufRead, Lines };
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::error::{ PuzzleError, Result };
+use crate::input::Normalize;
+use crate::params::{ Param, ParamError, Params };
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// The part has no puzzle to solve (e.g. the second half of day 25).
+    Empty,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Empty => Ok(()),
+        }
+    }
+}
+
+impl From<&Answer> for Value {
+    fn from(answer: &Answer) -> Self {
+        match answer {
+            Answer::Number(n) => Value::from(*n),
+            Answer::Text(s) => Value::from(s.as_str()),
+            Answer::Empty => Value::Null,
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Number(n)
+    }
+}
+
+impl From<u16> for Answer {
+    fn from(n: u16) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self {
+        Answer::Number(i64::try_from(n).expect("answer does not fit in an i64"))
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Number(i64::try_from(n).expect("answer does not fit in an i64"))
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(String::from(s))
+    }
+}
+
+/// A picture of how a puzzle was solved, drawn as text to be shown in a monospace
+/// font, such as the final state of a grid of lights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visual {
+    pub title: String,
+    pub text: String,
+}
+
+impl Visual {
+    pub fn new(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Visual { title: title.into(), text: text.into() }
+    }
+}
+
+/// A day-specific question the REPL can answer about a parsed input, such as the
+/// signal on one wire of a day 7 circuit.
+#[derive(Debug, Clone, Copy)]
+pub struct Query {
+    pub name: &'static str,
+    /// The arguments it takes, for help text, like `<steps> [part]`.
+    pub args: &'static str,
+    pub help: &'static str,
+}
+
+/// Why a query, or any other REPL command, could not be answered.
+#[derive(Debug)]
+pub enum QueryError {
+    UnknownCommand(String),
+    /// A command was given the wrong arguments.
+    Usage(String),
+    Param(ParamError),
+    Puzzle(PuzzleError),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::UnknownCommand(command) => write!(f, "unknown command '{}', try 'help'", command),
+            QueryError::Usage(message) => write!(f, "{}", message),
+            QueryError::Param(e) => write!(f, "{}", e),
+            QueryError::Puzzle(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for QueryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            QueryError::Param(e) => Some(e),
+            QueryError::Puzzle(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParamError> for QueryError {
+    fn from(e: ParamError) -> Self {
+        QueryError::Param(e)
+    }
+}
+
+impl From<PuzzleError> for QueryError {
+    fn from(e: PuzzleError) -> Self {
+        QueryError::Puzzle(e)
+    }
+}
+
+/// Parses the argument at `index` of a query, which is described as `name` if it
+/// is missing or invalid.
+pub fn query_argument<T: FromStr>(args: &[&str], index: usize, name: &str) -> std::result::Result<T, QueryError> {
+    let arg = args.get(index)
+        .ok_or_else(|| QueryError::Usage(format!("missing argument: {}", name)))?;
+    arg.parse()
+        .map_err(|_| QueryError::Usage(format!("invalid {}: '{}'", name, arg)))
+}
+
+/// A solver for one day's puzzle.
+///
+/// The input is parsed once, and both parts are computed from the parsed
+/// representation so that each part can be run (and timed) on its own.
+/// Tuning constants are declared as parameters and passed to each part,
+/// so that variants of the puzzle can be solved without editing the code.
+pub trait Solution {
+    /// The puzzle input after parsing. It is shown with `{:#?}` by the `dump`
+    /// command, to check that the input was parsed as expected.
+    type Input: fmt::Debug;
+
+    /// The parameters the parts read from their [`Params`], if any.
+    const PARAMS: &'static [Param] = &[];
+
+    /// How the raw input is cleaned up before it is passed to [`parse`](Self::parse).
+    const NORMALIZE: Normalize = Normalize::DEFAULT;
+
+    /// Parses the puzzle input, which may come from a file, stdin or a string,
+    /// after it has been normalized.
+    fn parse<R: BufRead>(input: Lines<R>) -> Result<Self::Input>;
+
+    fn part1(input: &Self::Input, params: &Params) -> Result<Answer>;
+
+    fn part2(input: &Self::Input, params: &Params) -> Result<Answer>;
+
+    /// Pictures of the solution for reports, if the puzzle has anything worth
+    /// drawing. Most days do not.
+    fn visualize(_input: &Self::Input, _params: &Params) -> Result<Vec<Visual>> {
+        Ok(Vec::new())
+    }
+
+    /// Questions about the input that the REPL can ask on top of solving it, each
+    /// answered by [`query`](Self::query).
+    const QUERIES: &'static [Query] = &[];
+
+    /// Answers the query called `name`, one of [`QUERIES`](Self::QUERIES), given
+    /// the words that followed it.
+    fn query(_input: &Self::Input, _params: &Params, name: &str, _args: &[&str]) -> std::result::Result<String, QueryError> {
+        Err(QueryError::UnknownCommand(name.to_string()))
+    }
+}
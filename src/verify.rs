@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use toml::Value;
+
+use crate::solution::Answer;
+
+/// Known-good answers, keyed by day and part.
+///
+/// The answers file is TOML with one table per day:
+///
+/// ```toml
+/// [day01]
+/// part1 = 138
+/// part2 = 1771
+///
+/// [day11]
+/// part1 = "hepxxyzz"
+/// ```
+#[derive(Debug, Default)]
+pub struct ExpectedAnswers {
+    answers: HashMap<(u32, u8), String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    /// There is no expected answer to compare against.
+    Missing,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "PASS"),
+            Verdict::Fail => write!(f, "FAIL"),
+            Verdict::Missing => write!(f, "MISSING"),
+        }
+    }
+}
+
+impl ExpectedAnswers {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        Self::from_toml(&text)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+        let table: toml::Table = text.parse()?;
+        let mut answers = HashMap::new();
+
+        for (key, parts) in table {
+            let day = key.strip_prefix("day")
+                .and_then(|d| d.parse::<u32>().ok())
+                .ok_or_else(|| format!("Expected a table named like 'day01', found '{}'", key))?;
+            let parts = parts.as_table()
+                .ok_or_else(|| format!("Expected '{}' to be a table of answers", key))?;
+
+            for (part_key, value) in parts {
+                let part = match part_key.as_str() {
+                    "part1" => 1,
+                    "part2" => 2,
+                    _ => return Err(format!("Unexpected key '{}' in [{}]", part_key, key).into()),
+                };
+                let answer = match value {
+                    Value::Integer(n) => n.to_string(),
+                    Value::String(s) => s.clone(),
+                    _ => return Err(format!("Answer for {}.{} must be an integer or a string", key, part_key).into()),
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+
+        Ok(ExpectedAnswers { answers })
+    }
+
+    pub fn get(&self, day: u32, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|s| s.as_str())
+    }
+
+    pub fn check(&self, day: u32, part: u8, actual: &Answer) -> Verdict {
+        match self.get(day, part) {
+            Some(expected) if expected == actual.to_string() => Verdict::Pass,
+            Some(_) => Verdict::Fail,
+            None => Verdict::Missing,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let expected = ExpectedAnswers::from_toml(r#"
+            [day01]
+            part1 = 138
+            part2 = 1771
+
+            [day11]
+            part1 = "hepxxyzz"
+        "#).unwrap();
+
+        assert_eq!(Some("138"), expected.get(1, 1));
+        assert_eq!(Some("1771"), expected.get(1, 2));
+        assert_eq!(Some("hepxxyzz"), expected.get(11, 1));
+        assert_eq!(None, expected.get(11, 2));
+        assert_eq!(None, expected.get(2, 1));
+    }
+
+    #[test]
+    fn test_from_toml_rejects_bad_keys() {
+        assert!(ExpectedAnswers::from_toml("[first]\npart1 = 1").is_err());
+        assert!(ExpectedAnswers::from_toml("[day01]\npart3 = 1").is_err());
+        assert!(ExpectedAnswers::from_toml("[day01]\npart1 = 1.5").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = ExpectedAnswers::from_toml("[day01]\npart1 = -3\n[day11]\npart1 = \"abcdffaa\"").unwrap();
+
+        assert_eq!(Verdict::Pass, expected.check(1, 1, &Answer::Number(-3)));
+        assert_eq!(Verdict::Fail, expected.check(1, 1, &Answer::Number(3)));
+        assert_eq!(Verdict::Missing, expected.check(1, 2, &Answer::Number(3)));
+        assert_eq!(Verdict::Pass, expected.check(11, 1, &Answer::Text(String::from("abcdffaa"))));
+    }
+}
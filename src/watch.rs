@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::thread;
+use std::time::{ Duration, SystemTime };
+
+use crate::runner::Run;
+use crate::solution::Answer;
+
+/// How often [`FileWatcher::wait`] looks at the file.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Notices when a file changes, by polling its modification time and size.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    /// The modification time and size the file had when last polled.
+    seen: Option<(SystemTime, u64)>,
+}
+
+impl FileWatcher {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        FileWatcher { path: path.as_ref().to_path_buf(), seen: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file has changed since the last poll, which is always the case
+    /// on the first poll. A file that cannot be read, such as while an editor is
+    /// replacing it, counts as unchanged until it is back.
+    pub fn poll(&mut self) -> bool {
+        let current = fs::metadata(&self.path)
+            .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+            .ok();
+        match current {
+            Some(current) if self.seen != Some(current) => {
+                self.seen = Some(current);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Blocks until the file changes, polling it every `interval`.
+    pub fn wait(&mut self, interval: Duration) {
+        while !self.poll() {
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// How the answer to a part compares with the one from an earlier run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<'a> {
+    /// The earlier run did not solve this part, or there was no earlier run.
+    New,
+    Unchanged,
+    Changed { was: &'a Answer },
+}
+
+impl<'a> Change<'a> {
+    /// Compares `answer` with the answer to `part` in `previous`.
+    pub fn between(previous: Option<&'a Run>, part: u8, answer: &Answer) -> Self {
+        match previous.and_then(|run| run.solved().find(|&(p, _)| p == part)) {
+            None => Change::New,
+            Some((_, was)) if was.answer == *answer => Change::Unchanged,
+            Some((_, was)) => Change::Changed { was: &was.answer },
+        }
+    }
+
+    /// The earlier answer, if there was one.
+    pub fn previous(&self, answer: &'a Answer) -> Option<&'a Answer> {
+        match self {
+            Change::New => None,
+            Change::Unchanged => Some(answer),
+            Change::Changed { was } => Some(was),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use super::*;
+    use crate::runner::PartRun;
+
+    fn run(part1: Option<i64>, part2: Option<i64>) -> Run {
+        let part_run = |answer: i64| PartRun { answer: Answer::Number(answer), time: Duration::ZERO };
+        Run { parse_time: Duration::ZERO, part1: part1.map(|answer| Ok(part_run(answer))), part2: part2.map(|answer| Ok(part_run(answer))) }
+    }
+
+    #[test]
+    fn test_poll() {
+        let path = std::env::temp_dir().join(format!("advent-of-code-2015-watch-{}.txt", std::process::id()));
+        fs::write(&path, "1").unwrap();
+        let mut watcher = FileWatcher::new(&path);
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        // Set the time explicitly, as file systems can store it too coarsely to tell writes apart
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(watcher.poll());
+        fs::write(&path, "12").unwrap();
+        assert!(watcher.poll());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll());
+    }
+
+    #[test]
+    fn test_change() {
+        let previous = run(Some(1), None);
+        assert_eq!(Change::New, Change::between(None, 1, &Answer::Number(1)));
+        assert_eq!(Change::Unchanged, Change::between(Some(&previous), 1, &Answer::Number(1)));
+        assert_eq!(Change::Changed { was: &Answer::Number(1) }, Change::between(Some(&previous), 1, &Answer::Number(2)));
+        assert_eq!(Change::New, Change::between(Some(&previous), 2, &Answer::Number(1)));
+    }
+}